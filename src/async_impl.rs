@@ -0,0 +1,97 @@
+use std::{io, path::Path};
+use crate::FileID;
+
+impl FileID {
+	/// Obtains the identifier of a path without blocking the tokio runtime,
+	/// see [`FileID::new`] for more information.
+	/// 
+	/// The identifier is obtained on tokio's thread pool for blocking operations,
+	/// so this function must be called from within a tokio runtime.
+	/// 
+	/// Async file handles (such as `tokio::fs::File`) implement [`AsRawFd`]
+	/// (or [`AsRawHandle`] on Windows), so they can already be identified with [`FileID::new`],
+	/// which does not block as the file is already open.
+	/// 
+	/// # Errors
+	/// 
+	/// This function will error if the path does not exist
+	/// or it fails to obtain the metadata containing the identifier.
+	/// 
+	/// # Examples
+	/// 
+	/// ```rust,no_run
+	/// use fs_id::FileID;
+	/// 
+	/// async fn identify() -> std::io::Result<()> {
+	///     let file_id1 = FileID::new_tokio("/some/file/path.txt").await?;
+	///     let file_id2 = FileID::new_tokio("/some/other/file.txt").await?;
+	///     println!("{}", file_id1 == file_id2);
+	///     Ok(())
+	/// }
+	/// ```
+	/// 
+	/// [`AsRawFd`]: std::os::fd::AsRawFd
+	/// [`AsRawHandle`]: https://doc.rust-lang.org/std/os/windows/io/trait.AsRawHandle.html
+	#[cfg(feature = "tokio")]
+	pub async fn new_tokio<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+		let path = path.as_ref().to_path_buf();
+		tokio::task::spawn_blocking(move || Self::new(path.as_path()))
+			.await
+			.map_err(|error| io::Error::new(io::ErrorKind::Other, error))?
+	}
+
+	/// Obtains the identifier of a path without blocking the async-std runtime,
+	/// see [`FileID::new`] for more information.
+	/// 
+	/// The identifier is obtained on async-std's thread pool for blocking operations.
+	/// 
+	/// Async file handles (such as `async_std::fs::File`) implement [`AsRawFd`]
+	/// (or [`AsRawHandle`] on Windows), so they can already be identified with [`FileID::new`],
+	/// which does not block as the file is already open.
+	/// 
+	/// # Errors
+	/// 
+	/// This function will error if the path does not exist
+	/// or it fails to obtain the metadata containing the identifier.
+	/// 
+	/// # Examples
+	/// 
+	/// ```rust,no_run
+	/// use fs_id::FileID;
+	/// 
+	/// async fn identify() -> std::io::Result<()> {
+	///     let file_id1 = FileID::new_async_std("/some/file/path.txt").await?;
+	///     let file_id2 = FileID::new_async_std("/some/other/file.txt").await?;
+	///     println!("{}", file_id1 == file_id2);
+	///     Ok(())
+	/// }
+	/// ```
+	/// 
+	/// [`AsRawFd`]: std::os::fd::AsRawFd
+	/// [`AsRawHandle`]: https://doc.rust-lang.org/std/os/windows/io/trait.AsRawHandle.html
+	#[cfg(feature = "async-std")]
+	pub async fn new_async_std<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+		let path = path.as_ref().to_path_buf();
+		async_std::task::spawn_blocking(move || Self::new(path.as_path())).await
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::FileID;
+
+	#[test]
+	#[cfg(feature = "tokio")]
+	fn check_tokio() -> std::io::Result<()> {
+		let runtime = tokio::runtime::Builder::new_current_thread().build()?;
+		assert_eq!(runtime.block_on(FileID::new_tokio("Cargo.toml"))?, FileID::new("Cargo.toml")?);
+		Ok(())
+	}
+
+	#[test]
+	#[cfg(feature = "async-std")]
+	fn check_async_std() -> std::io::Result<()> {
+		assert_eq!(async_std::task::block_on(FileID::new_async_std("Cargo.toml"))?, FileID::new("Cargo.toml")?);
+		Ok(())
+	}
+}
@@ -0,0 +1,379 @@
+//! Collections keyed by [`FileID`], which keep track of the paths each file was reached through.
+//! 
+//! Since multiple paths can point to the same file (for example hard links),
+//! these collections group the paths by their identifier, so that all the aliases of a file can be found.
+//! 
+//! # Examples
+//! 
+//! ```rust,no_run
+//! use fs_id::collections::FileIDSet;
+//! 
+//! fn main() -> std::io::Result<()> {
+//!     let mut files = FileIDSet::new();
+//!     files.insert_path("/some/file/path.txt")?;
+//!     files.insert_path("/some/hard/link.txt")?;
+//!     assert!(files.contains_path("/some/hard/link.txt")?);
+//!     for path in files.aliases_of("/some/file/path.txt")? {
+//!         println!("{}", path.display());
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+use std::{collections::HashMap, hash::{BuildHasherDefault, Hasher}, io, path::{Path, PathBuf}};
+use crate::FileID;
+
+/// A fast, non-cryptographic [`Hasher`] for [`FileID`]s.
+/// 
+/// `FileID`s are already unique numbers, so there is no need for the DoS resistance
+/// of the default hasher, which is considerably slower.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FileIDHasher(u64);
+
+impl FileIDHasher {
+	const SEED: u64 = 0xf135_7aae_2e62_65a5;
+
+	fn add(&mut self, value: u64) {
+		self.0 = (self.0.rotate_left(5) ^ value).wrapping_mul(Self::SEED);
+	}
+}
+
+impl Hasher for FileIDHasher {
+	fn write(&mut self, bytes: &[u8]) {
+		for chunk in bytes.chunks(8) {
+			let mut buf = [0; 8];
+			buf[..chunk.len()].copy_from_slice(chunk);
+			self.add(u64::from_le_bytes(buf));
+		}
+	}
+
+	fn write_u64(&mut self, value: u64) {
+		self.add(value);
+	}
+
+	fn write_u128(&mut self, value: u128) {
+		self.add(value as u64);
+		self.add((value >> 64) as u64);
+	}
+
+	fn finish(&self) -> u64 {
+		// The multiplication leaves the most entropy in the high bits, move some of it to the low ones.
+		self.0.rotate_left(26)
+	}
+}
+
+/// The [`BuildHasher`](std::hash::BuildHasher) for [`FileIDHasher`].
+pub type BuildFileIDHasher = BuildHasherDefault<FileIDHasher>;
+
+#[derive(Debug, Clone)]
+struct Entry<V> {
+	value: V,
+	paths: Vec<PathBuf>,
+}
+
+/// A map from [`FileID`]s to values, which also records the paths used to insert each value.
+#[derive(Debug, Clone)]
+pub struct FileIDMap<V> {
+	entries: HashMap<FileID, Entry<V>, BuildFileIDHasher>,
+}
+
+impl<V> FileIDMap<V> {
+	/// Creates an empty map.
+	#[must_use]
+	pub fn new() -> Self {
+		Self { entries: HashMap::default() }
+	}
+
+	/// Returns the number of distinct files in the map.
+	#[must_use]
+	pub fn len(&self) -> usize {
+		self.entries.len()
+	}
+
+	/// Returns `true` if the map contains no files.
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.entries.is_empty()
+	}
+
+	/// Inserts a value for a file identifier, returning the previous value if there was one.
+	/// 
+	/// The paths already recorded for the file are kept.
+	pub fn insert(&mut self, id: FileID, value: V) -> Option<V> {
+		match self.entries.get_mut(&id) {
+			Some(entry) => Some(std::mem::replace(&mut entry.value, value)),
+			None => {
+				self.entries.insert(id, Entry { value, paths: Vec::new() });
+				None
+			}
+		}
+	}
+
+	/// Inserts a value for the file the path points to, returning the previous value if there was one.
+	/// 
+	/// The path is recorded as one of the aliases of the file, unless it already was.
+	/// 
+	/// # Errors
+	/// 
+	/// Returns [`io::Error`] when failing to obtain the identifier of the path.
+	pub fn insert_path<P: AsRef<Path> + ?Sized>(&mut self, path: &P, value: V) -> io::Result<Option<V>> {
+		let path = path.as_ref();
+		let id = FileID::new(path)?;
+		let previous = self.insert(id, value);
+		let paths = &mut self.entries.get_mut(&id).unwrap().paths;
+		if !paths.iter().any(|alias| alias == path) {
+			paths.push(path.to_path_buf());
+		}
+		Ok(previous)
+	}
+
+	/// Records a path for a file identifier that is already in the map, without obtaining its identifier again.
+	pub(crate) fn push_path(&mut self, id: &FileID, path: PathBuf) {
+		if let Some(entry) = self.entries.get_mut(id) {
+			entry.paths.push(path);
+		}
+	}
+
+	/// Returns a reference to the value of a file identifier.
+	#[must_use]
+	pub fn get(&self, id: &FileID) -> Option<&V> {
+		self.entries.get(id).map(|entry| &entry.value)
+	}
+
+	/// Returns a mutable reference to the value of a file identifier.
+	pub fn get_mut(&mut self, id: &FileID) -> Option<&mut V> {
+		self.entries.get_mut(id).map(|entry| &mut entry.value)
+	}
+
+	/// Returns a reference to the value of the file the path points to.
+	/// 
+	/// # Errors
+	/// 
+	/// Returns [`io::Error`] when failing to obtain the identifier of the path.
+	pub fn get_path<P: AsRef<Path> + ?Sized>(&self, path: &P) -> io::Result<Option<&V>> {
+		Ok(self.get(&FileID::new(path.as_ref())?))
+	}
+
+	/// Returns `true` if the map contains a value for the file identifier.
+	#[must_use]
+	pub fn contains(&self, id: &FileID) -> bool {
+		self.entries.contains_key(id)
+	}
+
+	/// Returns `true` if the map contains a value for the file the path points to,
+	/// even if it was inserted through a different path.
+	/// 
+	/// # Errors
+	/// 
+	/// Returns [`io::Error`] when failing to obtain the identifier of the path.
+	pub fn contains_path<P: AsRef<Path> + ?Sized>(&self, path: &P) -> io::Result<bool> {
+		Ok(self.contains(&FileID::new(path.as_ref())?))
+	}
+
+	/// Removes a file identifier from the map, returning its value if it was present.
+	pub fn remove(&mut self, id: &FileID) -> Option<V> {
+		self.entries.remove(id).map(|entry| entry.value)
+	}
+
+	/// Returns the paths recorded for a file identifier,
+	/// which is empty if the file is not in the map or it was only inserted by identifier.
+	#[must_use]
+	pub fn paths(&self, id: &FileID) -> &[PathBuf] {
+		self.entries.get(id).map_or(&[], |entry| &entry.paths)
+	}
+
+	/// Returns all the recorded paths that point to the same file as the given path,
+	/// which may include the path itself.
+	/// 
+	/// # Errors
+	/// 
+	/// Returns [`io::Error`] when failing to obtain the identifier of the path.
+	pub fn aliases_of<P: AsRef<Path> + ?Sized>(&self, path: &P) -> io::Result<&[PathBuf]> {
+		Ok(self.paths(&FileID::new(path.as_ref())?))
+	}
+
+	/// Returns an iterator over the file identifiers and their values.
+	pub fn iter(&self) -> impl Iterator<Item = (&FileID, &V)> {
+		self.entries.iter().map(|(id, entry)| (id, &entry.value))
+	}
+
+	/// Returns an iterator over the file identifiers, their values, and their recorded paths.
+	pub fn iter_with_paths(&self) -> impl Iterator<Item = (&FileID, &V, &[PathBuf])> {
+		self.entries.iter().map(|(id, entry)| (id, &entry.value, entry.paths.as_slice()))
+	}
+
+	/// Creates a map from pairs of paths and values, see [`FileIDMap::insert_path`].
+	/// 
+	/// # Errors
+	/// 
+	/// Returns [`io::Error`] when failing to obtain the identifier of any of the paths.
+	pub fn from_paths<I: IntoIterator<Item = (P, V)>, P: AsRef<Path>>(paths: I) -> io::Result<Self> {
+		let mut map = Self::new();
+		for (path, value) in paths {
+			map.insert_path(&path, value)?;
+		}
+		Ok(map)
+	}
+}
+
+impl<V> Default for FileIDMap<V> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<V> Extend<(FileID, V)> for FileIDMap<V> {
+	fn extend<I: IntoIterator<Item = (FileID, V)>>(&mut self, iter: I) {
+		for (id, value) in iter {
+			self.insert(id, value);
+		}
+	}
+}
+
+impl<V> FromIterator<(FileID, V)> for FileIDMap<V> {
+	fn from_iter<I: IntoIterator<Item = (FileID, V)>>(iter: I) -> Self {
+		let mut map = Self::new();
+		map.extend(iter);
+		map
+	}
+}
+
+/// A set of [`FileID`]s, which also records the paths used to insert each identifier.
+#[derive(Debug, Clone, Default)]
+pub struct FileIDSet {
+	map: FileIDMap<()>,
+}
+
+impl FileIDSet {
+	/// Creates an empty set.
+	#[must_use]
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Returns the number of distinct files in the set.
+	#[must_use]
+	pub fn len(&self) -> usize {
+		self.map.len()
+	}
+
+	/// Returns `true` if the set contains no files.
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.map.is_empty()
+	}
+
+	/// Inserts a file identifier, returning `true` if it was not already present.
+	pub fn insert(&mut self, id: FileID) -> bool {
+		self.map.insert(id, ()).is_none()
+	}
+
+	/// Inserts the file the path points to, returning `true` if the file was not already present.
+	/// 
+	/// The path is recorded as one of the aliases of the file, unless it already was.
+	/// 
+	/// # Errors
+	/// 
+	/// Returns [`io::Error`] when failing to obtain the identifier of the path.
+	pub fn insert_path<P: AsRef<Path> + ?Sized>(&mut self, path: &P) -> io::Result<bool> {
+		Ok(self.map.insert_path(path, ())?.is_none())
+	}
+
+	/// Returns `true` if the set contains the file identifier.
+	#[must_use]
+	pub fn contains(&self, id: &FileID) -> bool {
+		self.map.contains(id)
+	}
+
+	/// Returns `true` if the set contains the file the path points to,
+	/// even if it was inserted through a different path.
+	/// 
+	/// # Errors
+	/// 
+	/// Returns [`io::Error`] when failing to obtain the identifier of the path.
+	pub fn contains_path<P: AsRef<Path> + ?Sized>(&self, path: &P) -> io::Result<bool> {
+		self.map.contains_path(path)
+	}
+
+	/// Removes a file identifier from the set, returning `true` if it was present.
+	pub fn remove(&mut self, id: &FileID) -> bool {
+		self.map.remove(id).is_some()
+	}
+
+	/// Returns the paths recorded for a file identifier, see [`FileIDMap::paths`].
+	#[must_use]
+	pub fn paths(&self, id: &FileID) -> &[PathBuf] {
+		self.map.paths(id)
+	}
+
+	/// Returns all the recorded paths that point to the same file as the given path,
+	/// see [`FileIDMap::aliases_of`].
+	/// 
+	/// # Errors
+	/// 
+	/// Returns [`io::Error`] when failing to obtain the identifier of the path.
+	pub fn aliases_of<P: AsRef<Path> + ?Sized>(&self, path: &P) -> io::Result<&[PathBuf]> {
+		self.map.aliases_of(path)
+	}
+
+	/// Returns an iterator over the file identifiers.
+	pub fn iter(&self) -> impl Iterator<Item = &FileID> {
+		self.map.iter().map(|(id, _)| id)
+	}
+
+	/// Returns an iterator over the file identifiers and their recorded paths.
+	pub fn iter_with_paths(&self) -> impl Iterator<Item = (&FileID, &[PathBuf])> {
+		self.map.iter_with_paths().map(|(id, _, paths)| (id, paths))
+	}
+
+	/// Creates a set from multiple paths, see [`FileIDSet::insert_path`].
+	/// 
+	/// # Errors
+	/// 
+	/// Returns [`io::Error`] when failing to obtain the identifier of any of the paths.
+	pub fn from_paths<I: IntoIterator<Item = P>, P: AsRef<Path>>(paths: I) -> io::Result<Self> {
+		Ok(Self { map: FileIDMap::from_paths(paths.into_iter().map(|path| (path, ())))? })
+	}
+}
+
+impl Extend<FileID> for FileIDSet {
+	fn extend<I: IntoIterator<Item = FileID>>(&mut self, iter: I) {
+		self.map.extend(iter.into_iter().map(|id| (id, ())));
+	}
+}
+
+impl FromIterator<FileID> for FileIDSet {
+	fn from_iter<I: IntoIterator<Item = FileID>>(iter: I) -> Self {
+		let mut set = Self::new();
+		set.extend(iter);
+		set
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::fs;
+	use crate::FileID;
+	use super::{FileIDMap, FileIDSet};
+
+	#[test]
+	fn check_aliases() -> std::io::Result<()> {
+		let dir = std::env::temp_dir().join(format!("fs-id-aliases-{}", std::process::id()));
+		fs::create_dir_all(&dir)?;
+		let (file, link) = (dir.join("file"), dir.join("link"));
+		fs::write(&file, "")?;
+		fs::hard_link(&file, &link)?;
+		let id = FileID::new(file.as_path());
+		let set = FileIDSet::from_paths([&file, &link, &file, &dir]);
+		let map = FileIDMap::from_paths([(&file, 1), (&link, 2)]);
+		fs::remove_dir_all(&dir)?;
+		let (id, set, map) = (id?, set?, map?);
+		assert_eq!(set.len(), 2);
+		assert_eq!(set.paths(&id), [file.clone(), link.clone()]);
+		assert_eq!(map.len(), 1);
+		assert_eq!(map.get(&id), Some(&2));
+		assert_eq!(map.paths(&id), [file, link]);
+		assert!(!set.contains(&FileID::new("Cargo.toml")?));
+		Ok(())
+	}
+}
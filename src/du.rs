@@ -0,0 +1,169 @@
+//! Disk usage calculation that counts every file once, no matter how many hard links it has.
+//! 
+//! # Examples
+//! 
+//! ```rust,no_run
+//! use fs_id::du::disk_usage;
+//! 
+//! fn main() -> std::io::Result<()> {
+//!     let usage = disk_usage("/some/directory", false)?;
+//!     println!("{} files, {} bytes on disk", usage.files(), usage.disk_size());
+//!     Ok(())
+//! }
+//! ```
+
+use std::{fs::Metadata, io, path::Path};
+use crate::walk::{Event, Walker};
+
+/// The space used by a directory tree, see [`disk_usage`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DiskUsage {
+	files: u64,
+	apparent_size: u64,
+	disk_size: u64,
+}
+
+impl DiskUsage {
+	/// Calculates the space used by the files found by a [`Walker`].
+	/// 
+	/// Every file (including directories) is counted only once,
+	/// even if it is found at multiple paths.
+	/// 
+	/// # Errors
+	/// 
+	/// Returns the first [`io::Error`] encountered during the traversal,
+	/// see [`DiskUsage::from_walker_lossy`] to skip the entries that cannot be read instead.
+	/// 
+	/// # Examples
+	/// 
+	/// ```rust,no_run
+	/// use fs_id::{du::DiskUsage, walk::Walker};
+	/// 
+	/// fn main() -> std::io::Result<()> {
+	///     let usage = DiskUsage::from_walker(Walker::new("/some/directory").follow_links(true))?;
+	///     println!("{} bytes", usage.apparent_size());
+	///     Ok(())
+	/// }
+	/// ```
+	pub fn from_walker(walker: Walker) -> io::Result<Self> {
+		let mut usage = Self::default();
+		for event in walker {
+			usage.add(&event?);
+		}
+		Ok(usage)
+	}
+
+	/// Calculates the space used by the files found by a [`Walker`],
+	/// skipping the entries that cannot be read (for example because of missing permissions), like `du` does.
+	/// 
+	/// Every error encountered is passed to `on_error`, and the returned usage only includes the files that could be read.
+	/// 
+	/// # Examples
+	/// 
+	/// ```rust,no_run
+	/// use fs_id::{du::DiskUsage, walk::Walker};
+	/// 
+	/// let usage = DiskUsage::from_walker_lossy(Walker::new("/some/directory"), |error| eprintln!("{error}"));
+	/// println!("at least {} bytes", usage.apparent_size());
+	/// ```
+	pub fn from_walker_lossy<F: FnMut(io::Error)>(walker: Walker, mut on_error: F) -> Self {
+		let mut usage = Self::default();
+		for event in walker {
+			match event {
+				Ok(event) => usage.add(&event),
+				Err(error) => on_error(error),
+			}
+		}
+		usage
+	}
+
+	fn add(&mut self, event: &Event) {
+		if let Event::File(entry) = event {
+			self.add_metadata(entry.metadata());
+		}
+	}
+
+	fn add_metadata(&mut self, metadata: &Metadata) {
+		self.files += 1;
+		self.apparent_size += metadata.len();
+		self.disk_size += allocated_size(metadata);
+	}
+
+	/// Returns the number of distinct files, including directories.
+	#[must_use]
+	pub const fn files(&self) -> u64 {
+		self.files
+	}
+
+	/// Returns the sum of the sizes of the files, like `du --apparent-size`.
+	#[must_use]
+	pub const fn apparent_size(&self) -> u64 {
+		self.apparent_size
+	}
+
+	/// Returns the space actually allocated for the files on disk, like `du`.
+	/// 
+	/// # Platform-specific behavior
+	/// 
+	/// This is the sum of `st_blocks` multiplied by 512 on Unix,
+	/// while on other platforms it is the same as [`DiskUsage::apparent_size`].
+	/// 
+	/// This may change in the future.
+	#[must_use]
+	pub const fn disk_size(&self) -> u64 {
+		self.disk_size
+	}
+}
+
+#[cfg(unix)]
+fn allocated_size(metadata: &Metadata) -> u64 {
+	use std::os::unix::fs::MetadataExt;
+	metadata.blocks() * 512
+}
+
+#[cfg(not(unix))]
+fn allocated_size(metadata: &Metadata) -> u64 {
+	metadata.len()
+}
+
+/// Calculates the space used by a directory tree, counting every file only once,
+/// even if it has multiple hard links inside the tree.
+/// 
+/// Symbolic links are not followed, and if `same_storage` is `true`
+/// other filesystems mounted inside the tree are skipped, see [`Walker::same_storage`].
+/// 
+/// # Errors
+/// 
+/// Returns the first [`io::Error`] encountered during the traversal.
+pub fn disk_usage<P: AsRef<Path>>(root: P, same_storage: bool) -> io::Result<DiskUsage> {
+	DiskUsage::from_walker(Walker::new(root).same_storage(same_storage))
+}
+
+#[cfg(test)]
+mod tests {
+	use std::fs;
+	use crate::walk::Walker;
+	use super::{disk_usage, DiskUsage};
+
+	#[test]
+	fn check_hard_links_counted_once() -> std::io::Result<()> {
+		let dir = std::env::temp_dir().join(format!("fs-id-du-{}", std::process::id()));
+		fs::create_dir_all(&dir)?;
+		fs::write(dir.join("file"), [0; 1000])?;
+		fs::hard_link(dir.join("file"), dir.join("link"))?;
+		let usage = disk_usage(&dir, true);
+		let dir_len = fs::metadata(&dir)?.len();
+		fs::remove_dir_all(&dir)?;
+		let usage = usage?;
+		assert_eq!(usage.files(), 2);
+		assert_eq!(usage.apparent_size(), dir_len + 1000);
+		Ok(())
+	}
+
+	#[test]
+	fn check_errors_skipped() {
+		let mut errors = 0;
+		let usage = DiskUsage::from_walker_lossy(Walker::new("missing"), |_| errors += 1);
+		assert_eq!((usage, errors), (DiskUsage::default(), 1));
+	}
+}
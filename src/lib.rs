@@ -1,10 +1,37 @@
-use std::{ffi::OsStr, fs::File, io, path::Path};
+use std::{error::Error, ffi::OsStr, fmt, fs::{File, OpenOptions}, io, path::Path, str::FromStr};
 
 #[cfg_attr(windows, path = "windows.rs")]
 #[cfg_attr(unix, path = "unix.rs")]
 #[cfg_attr(target_os = "wasi", path = "wasi.rs")]
 mod sys;
 
+#[cfg(feature = "serde")]
+mod serde_impl;
+
+#[cfg(any(feature = "tokio", feature = "async-std"))]
+mod async_impl;
+
+pub mod collections;
+
+pub mod du;
+
+#[cfg(target_os = "linux")]
+pub mod linux;
+
+#[cfg(target_os = "linux")]
+pub mod mounts;
+
+#[cfg(target_os = "linux")]
+pub mod process;
+
+pub mod snapshot;
+
+pub mod tracker;
+
+pub mod version;
+
+pub mod walk;
+
 /// A file's identifier, can be compared with other `FileID`s to check if 2 variables point to the same file.
 /// 
 /// This struct is the combination of 2 identifiers:
@@ -20,9 +47,21 @@ mod sys;
 /// the 3 will share the same identifier if they belong to the same process.
 /// (Only works on Unix, on Windows it will just error...)
 /// 
+/// `FileID` can also be converted to and from text with [`Display`] and [`FromStr`],
+/// using the storage identifier and the internal file identifier in hexadecimal, separated by a colon.  
+/// For example: `fe00:1060cb`.
+/// 
+/// When the `serde` feature is enabled, `FileID` implements `Serialize` and `Deserialize`.  
+/// The serialized form records the platform it was obtained on,
+/// and deserializing it on a different platform will fail.
+/// 
+/// `FileID`s are ordered by storage identifier first, and by internal file identifier second,
+/// so sorting them groups files by the storage that contains them.
+/// 
 /// [`AsRawFd`]: os::fd::AsRawFd
 /// [`AsRawHandle`]: os::windows::io::AsRawHandle
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// [`Display`]: fmt::Display
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct FileID (sys::FileIDImpl);
 
 impl FileID {
@@ -30,20 +69,22 @@ impl FileID {
 	/// 
 	/// # Platform-specific behavior
 	/// 
-	/// While on Unix obtaining the identifier of a directory is possible,
-	/// on Windows an error will be returned instead.
+	/// Paths are never opened for reading, so only search permission on their parent directories is required,
+	/// and directories can be identified on every platform.  
+	/// On Windows identifying a directory through an already open handle will still return an error,
+	/// unless the handle was opened with `FILE_FLAG_BACKUP_SEMANTICS`.
 	/// 
 	/// This function uses:
-	/// * `fstat64` on Unix
-	/// * `GetFileInformationByHandleEx` on Windows.
-	/// * `fd_filestat_get` on WASI.
+	/// * `fstat64` on Unix (`stat64` for paths).
+	/// * `GetFileInformationByHandleEx` on Windows (on a handle opened without any access rights for paths).
+	/// * `fd_filestat_get` on WASI (`path_filestat_get` for paths).
 	/// 
 	/// This may change in the future.
 	/// 
 	/// # Errors
-	///
-	/// This function will error if it fails to open the file
-	/// or fails to obtain the metadata containing the identifier.
+	/// 
+	/// This function will error if the path does not exist
+	/// or it fails to obtain the metadata containing the identifier.
 	/// 
 	/// # Examples
 	/// 
@@ -80,6 +121,42 @@ impl FileID {
 		file.get_id()
 	}
 
+	/// Obtains the identifier of a path without following it if it is a symbolic link.
+	/// 
+	/// Unlike [`FileID::new`], which would return the identifier of the file the link points to,
+	/// this returns the identifier of the link itself, allowing to tell a link apart from its target.
+	/// Symbolic links in the path's parent directories are still followed.
+	/// 
+	/// # Platform-specific behavior
+	/// 
+	/// This function uses:
+	/// * `lstat64` on Unix.
+	/// * `GetFileInformationByHandleEx` on a handle opened with `FILE_FLAG_OPEN_REPARSE_POINT` on Windows.
+	/// * `path_filestat_get` without `LOOKUPFLAGS_SYMLINK_FOLLOW` on WASI.
+	/// 
+	/// This may change in the future.
+	/// 
+	/// # Errors
+	/// 
+	/// This function will error if the path does not exist
+	/// or it fails to obtain the metadata containing the identifier.
+	/// 
+	/// # Examples
+	/// 
+	/// ```rust,no_run
+	/// use fs_id::FileID;
+	/// 
+	/// fn main() -> std::io::Result<()> {
+	///     let link_id = FileID::new_nofollow("/some/symlink")?;
+	///     let target_id = FileID::new("/some/symlink")?;
+	///     assert_ne!(link_id, target_id);
+	///     Ok(())
+	/// }
+	/// ```
+	pub fn new_nofollow<P: AsRef<Path> + ?Sized>(path: &P) -> io::Result<Self> {
+		sys::get_id_nofollow(path.as_ref())
+	}
+
 	/// Returns the storage identifier from the file identifier.
 	/// 
 	/// # Platform-specific behavior
@@ -137,8 +214,119 @@ impl FileID {
 	pub const fn internal_file_id(&self) -> u128 {
 		self.0.1 as u128
 	}
+
+	/// The length of the byte encoding returned by [`FileID::to_bytes`].
+	pub const BYTES_LEN: usize = 24;
+
+	/// Creates a file identifier from its storage identifier and internal file identifier,
+	/// as returned by [`FileID::storage_id`] and [`FileID::internal_file_id`].
+	/// 
+	/// Returns `None` if the internal file identifier cannot be represented on the current platform,
+	/// which happens on Unix and WASI when it does not fit in 64 bits.
+	/// 
+	/// Note that the identifiers are only meaningful on the system they were obtained on.
+	/// 
+	/// # Examples
+	/// 
+	/// ```rust,no_run
+	/// use fs_id::FileID;
+	/// 
+	/// fn main() -> std::io::Result<()> {
+	///     let file_id = FileID::new("/some/file/path.txt")?;
+	///     let rebuilt = FileID::from_raw_parts(file_id.storage_id(), file_id.internal_file_id());
+	///     assert_eq!(rebuilt, Some(file_id));
+	///     Ok(())
+	/// }
+	/// ```
+	#[must_use]
+	pub fn from_raw_parts(storage_id: u64, internal_file_id: u128) -> Option<Self> {
+		#[allow(clippy::useless_conversion)]
+		let internal_file_id = internal_file_id.try_into().ok()?;
+		Some(Self((storage_id, internal_file_id)))
+	}
+
+	/// Encodes the file identifier in [`FileID::BYTES_LEN`] bytes.
+	/// 
+	/// The first 8 bytes contain the storage identifier,
+	/// and the remaining 16 contain the internal file identifier, both in little endian.  
+	/// The encoding is the same on every platform, but the identifiers themselves are still platform-specific.
+	/// 
+	/// # Examples
+	/// 
+	/// ```rust,no_run
+	/// use fs_id::FileID;
+	/// 
+	/// fn main() -> std::io::Result<()> {
+	///     let file_id = FileID::new("/some/file/path.txt")?;
+	///     let bytes = file_id.to_bytes();
+	///     assert_eq!(FileID::from_bytes(bytes), Some(file_id));
+	///     Ok(())
+	/// }
+	/// ```
+	#[must_use]
+	pub fn to_bytes(&self) -> [u8; Self::BYTES_LEN] {
+		let mut bytes = [0; Self::BYTES_LEN];
+		bytes[..8].copy_from_slice(&self.storage_id().to_le_bytes());
+		bytes[8..].copy_from_slice(&self.internal_file_id().to_le_bytes());
+		bytes
+	}
+
+	/// Decodes a file identifier encoded with [`FileID::to_bytes`].
+	/// 
+	/// Returns `None` if the internal file identifier cannot be represented on the current platform,
+	/// see [`FileID::from_raw_parts`] for more information.
+	#[must_use]
+	pub fn from_bytes(bytes: [u8; Self::BYTES_LEN]) -> Option<Self> {
+		let (storage_id, internal_file_id) = bytes.split_at(8);
+		Self::from_raw_parts(
+			u64::from_le_bytes(storage_id.try_into().unwrap()),
+			u128::from_le_bytes(internal_file_id.try_into().unwrap())
+		)
+	}
+}
+
+impl fmt::Display for FileID {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{:x}:{:x}", self.storage_id(), self.internal_file_id())
+	}
 }
 
+impl FromStr for FileID {
+	type Err = ParseFileIDError;
+
+	/// Parses a file identifier in the format used by its [`Display`](fmt::Display) implementation.
+	/// 
+	/// Only lowercase hexadecimal digits without leading zeros are accepted,
+	/// so that every identifier has a single textual form.
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let (storage_id, internal_file_id) = s.split_once(':').ok_or(ParseFileIDError(()))?;
+		let is_lower_hex = |digits: &str| {
+			(digits == "0" || !digits.starts_with('0'))
+				&& !digits.is_empty()
+				&& digits.bytes().all(|digit| matches!(digit, b'0'..=b'9' | b'a'..=b'f'))
+		};
+		if !is_lower_hex(storage_id) || !is_lower_hex(internal_file_id) {
+			return Err(ParseFileIDError(()));
+		}
+		Self::from_raw_parts(
+			u64::from_str_radix(storage_id, 16).map_err(|_| ParseFileIDError(()))?,
+			u128::from_str_radix(internal_file_id, 16).map_err(|_| ParseFileIDError(()))?
+		).ok_or(ParseFileIDError(()))
+	}
+}
+
+/// The error returned when parsing a [`FileID`] from a string fails.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseFileIDError(());
+
+impl fmt::Display for ParseFileIDError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str("invalid file identifier, expected `<storage id>:<internal file id>` in hexadecimal")
+	}
+}
+
+impl Error for ParseFileIDError {}
+
 /// A trait to obtain the file identifier of an underlying object.
 pub trait GetID {
 	/// Obtains the file identifier, see [`FileID::new`] for more information.
@@ -157,7 +345,7 @@ macro_rules! impl_get_id {
 		$(
 			impl GetID for $type {
 				fn get_id(&self) -> io::Result<FileID> {
-					File::open(self)?.get_id()
+					sys::get_path_id(Path::new(self))
 				}
 			}
 		)+
@@ -191,6 +379,225 @@ pub fn compare_ids<T1: GetID + ?Sized, T2: GetID + ?Sized>(id1: &T1, id2: &T2) -
 	Ok(id1.get_id()? == id2.get_id()?)
 }
 
+/// Opens a file for reading, but only if it is still the file identified by `expected`.
+/// 
+/// The identifier is obtained from the opened file rather than from the path,
+/// so the check cannot be raced by replacing the file between validating the path and opening it.
+/// 
+/// # Errors
+/// 
+/// Returns [`io::Error`] when failing to open the file or to obtain its identifier.  
+/// If the file was opened but it is a different file, the error contains a [`MismatchError`],
+/// which can be obtained with [`io::Error::get_ref`] and [`downcast_ref`](https://doc.rust-lang.org/std/error/trait.Error.html#method.downcast_ref).
+/// 
+/// # Examples
+/// 
+/// ```rust,no_run
+/// use fs_id::{open_verified, FileID, MismatchError};
+/// 
+/// fn main() -> std::io::Result<()> {
+///     let expected = FileID::new("/some/file/path.txt")?;
+///     // ...
+///     match open_verified("/some/file/path.txt", expected) {
+///         Ok(file) => println!("still the same file: {file:?}"),
+///         Err(error) => match error.get_ref().and_then(|error| error.downcast_ref::<MismatchError>()) {
+///             Some(mismatch) => println!("the file was replaced by {}", mismatch.found()),
+///             None => return Err(error),
+///         },
+///     }
+///     Ok(())
+/// }
+/// ```
+pub fn open_verified<P: AsRef<Path> + ?Sized>(path: &P, expected: FileID) -> io::Result<File> {
+	open_verified_with(OpenOptions::new().read(true), path, expected)
+}
+
+/// Opens a file with the given options, but only if it is the file identified by `expected`,
+/// see [`open_verified`] for more information.
+/// 
+/// Note that the file is opened before it is checked,
+/// so options with side effects (such as `truncate`) will be applied even if the check fails.
+/// 
+/// # Errors
+/// 
+/// See [`open_verified`].
+pub fn open_verified_with<P: AsRef<Path> + ?Sized>(options: &OpenOptions, path: &P, expected: FileID) -> io::Result<File> {
+	let file = options.open(path)?;
+	let found = file.get_id()?;
+	if found == expected {
+		Ok(file)
+	} else {
+		Err(io::Error::new(io::ErrorKind::Other, MismatchError { expected, found }))
+	}
+}
+
+/// The error contained in the [`io::Error`] returned by [`open_verified`] when the opened file is not the expected one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MismatchError {
+	expected: FileID,
+	found: FileID,
+}
+
+impl MismatchError {
+	/// Returns the identifier the file was expected to have.
+	#[must_use]
+	pub const fn expected(&self) -> FileID {
+		self.expected
+	}
+
+	/// Returns the identifier of the file that was actually opened.
+	#[must_use]
+	pub const fn found(&self) -> FileID {
+		self.found
+	}
+}
+
+impl fmt::Display for MismatchError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "expected file {}, but found file {}", self.expected, self.found)
+	}
+}
+
+impl Error for MismatchError {}
+
+/// Returns `Ok(true)` if the 2 files are in the same storage, returning `Ok(false)` otherwise.
+/// 
+/// Renaming or hard linking a file is only possible within the same storage,
+/// otherwise it fails with `EXDEV` (`ERROR_NOT_SAME_DEVICE` on Windows) and the file has to be copied instead.  
+/// Being in the same storage does not guarantee that it is possible though:
+/// on Linux different mounts of the same filesystem (such as bind mounts) share the storage identifier,
+/// but renaming or linking across them still fails with `EXDEV`, see [`same_storage_for_destination`].
+/// 
+/// See [`FileID::storage_id`] for more information on the storage identifiers.
+/// 
+/// # Errors
+/// 
+/// Returns [`io::Error`] when failing to obtain any of the 2 identifiers.
+/// 
+/// # Examples
+/// 
+/// ```rust,no_run
+/// use fs_id::same_storage;
+/// 
+/// fn main() -> std::io::Result<()> {
+///     if !same_storage("/some/file/path.txt", "/some/other/directory")? {
+///         println!("the file cannot be hard linked in the other directory");
+///     }
+///     Ok(())
+/// }
+/// ```
+pub fn same_storage<T1: GetID + ?Sized, T2: GetID + ?Sized>(id1: &T1, id2: &T2) -> io::Result<bool> {
+	Ok(id1.get_id()?.storage_id() == id2.get_id()?.storage_id())
+}
+
+/// Returns `Ok(true)` if the file at `source` could be renamed or hard linked to `destination`
+/// without crossing storages, returning `Ok(false)` otherwise.
+/// 
+/// Unlike [`same_storage`], `destination` does not need to exist:
+/// its storage is the one of its nearest existing parent directory,
+/// which is where the new directory entry would be created.  
+/// Like renaming and linking, symbolic links are not followed for `source`.
+/// 
+/// # Platform-specific behavior
+/// 
+/// On Linux the mounts of `source` and `destination` are compared too (see [`linux::MountedFileID::mount_id`]),
+/// as renaming or linking across different mounts of the same filesystem (such as bind mounts) fails with `EXDEV`.
+/// On kernels older than 5.8, which do not report mount identifiers, only the storages are compared.
+/// 
+/// # Errors
+/// 
+/// Returns [`io::Error`] when failing to obtain the identifier of `source`,
+/// or of an existing parent directory of `destination`.
+/// 
+/// # Examples
+/// 
+/// ```rust,no_run
+/// use fs_id::same_storage_for_destination;
+/// 
+/// fn main() -> std::io::Result<()> {
+///     let (temp, destination) = ("/tmp/file.txt", "/some/new/directory/file.txt");
+///     std::fs::create_dir_all("/some/new/directory")?;
+///     if same_storage_for_destination(temp, destination)? {
+///         std::fs::rename(temp, destination)?;
+///     } else {
+///         std::fs::copy(temp, destination)?;
+///         std::fs::remove_file(temp)?;
+///     }
+///     Ok(())
+/// }
+/// ```
+pub fn same_storage_for_destination<P1: AsRef<Path> + ?Sized, P2: AsRef<Path> + ?Sized>(
+	source: &P1,
+	destination: &P2
+) -> io::Result<bool> {
+	let source_location = storage_and_mount(source.as_ref(), true)?;
+	let destination = destination.as_ref();
+	for ancestor in destination.parent().unwrap_or(destination).ancestors() {
+		let ancestor = if ancestor.as_os_str().is_empty() { Path::new(".") } else { ancestor };
+		match storage_and_mount(ancestor, false) {
+			Ok(location) => return Ok(location == source_location),
+			Err(error) if error.kind() == io::ErrorKind::NotFound => continue,
+			Err(error) => return Err(error),
+		}
+	}
+	Err(io::Error::new(io::ErrorKind::NotFound, "no parent directory of the destination exists"))
+}
+
+/// Returns the storage identifier of a path, and the identifier of its mount when available.
+#[cfg(target_os = "linux")]
+fn storage_and_mount(path: &Path, nofollow: bool) -> io::Result<(u64, Option<u64>)> {
+	let id = if nofollow { linux::MountedFileID::from_path_nofollow(path) } else { linux::MountedFileID::from_path(path) };
+	match id {
+		Ok(id) => Ok((id.file_id().storage_id(), Some(id.mount_id()))),
+		Err(error) if error.kind() == io::ErrorKind::Unsupported => Ok((storage_id(path, nofollow)?, None)),
+		Err(error) => Err(error),
+	}
+}
+
+/// Returns the storage identifier of a path, and the identifier of its mount when available.
+#[cfg(not(target_os = "linux"))]
+fn storage_and_mount(path: &Path, nofollow: bool) -> io::Result<(u64, Option<u64>)> {
+	Ok((storage_id(path, nofollow)?, None))
+}
+
+fn storage_id(path: &Path, nofollow: bool) -> io::Result<u64> {
+	let id = if nofollow { FileID::new_nofollow(path)? } else { FileID::new(path)? };
+	Ok(id.storage_id())
+}
+
+/// Obtains the identifiers of multiple paths and returns them sorted by identifier,
+/// each paired with its path.
+/// 
+/// Files are grouped by storage and sorted by internal file identifier within the same storage,
+/// which on many filesystems roughly matches the order the files are laid out on disk,
+/// making sequential reads of the files faster (especially on spinning disks).  
+/// Paths with the same identifier keep their original relative order.
+/// 
+/// # Errors
+/// 
+/// Returns [`io::Error`] when failing to obtain any of the identifiers.
+/// 
+/// # Examples
+/// 
+/// ```rust,no_run
+/// use fs_id::sort_paths_by_id;
+/// 
+/// fn main() -> std::io::Result<()> {
+///     for (file_id, path) in sort_paths_by_id(["b.txt", "a.txt", "c.txt"])? {
+///         println!("{file_id}: {path}");
+///     }
+///     Ok(())
+/// }
+/// ```
+pub fn sort_paths_by_id<I: IntoIterator<Item = P>, P: AsRef<Path>>(paths: I) -> io::Result<Vec<(FileID, P)>> {
+	let mut sorted = paths
+		.into_iter()
+		.map(|path| Ok((FileID::new(path.as_ref())?, path)))
+		.collect::<io::Result<Vec<_>>>()?;
+	sorted.sort_by_key(|(file_id, _)| *file_id);
+	Ok(sorted)
+}
+
 #[cfg(test)]
 mod tests {
 	use crate::FileID;
@@ -205,4 +612,107 @@ mod tests {
 		println!("id1: {id1:?}\nid2: {id2:?}\nid3: {id3:?}");
 		Ok(())
 	}
+
+	#[test]
+	fn check_directories() -> std::io::Result<()> {
+		assert_eq!(FileID::new("src")?, FileID::new("src/../src")?);
+		assert_ne!(FileID::new("src")?, FileID::new(".")?);
+		Ok(())
+	}
+
+	#[test]
+	fn check_round_trips() -> std::io::Result<()> {
+		let id = FileID::new("Cargo.toml")?;
+		assert_eq!(FileID::from_raw_parts(id.storage_id(), id.internal_file_id()), Some(id));
+		assert_eq!(FileID::from_bytes(id.to_bytes()), Some(id));
+		assert_eq!(id.to_string().parse(), Ok(id));
+		assert!("".parse::<FileID>().is_err());
+		assert!("fe00".parse::<FileID>().is_err());
+		assert!("fe00:-1".parse::<FileID>().is_err());
+		assert!("+fe:+1".parse::<FileID>().is_err());
+		assert!("FE:1".parse::<FileID>().is_err());
+		assert!("0fe:01".parse::<FileID>().is_err());
+		assert_eq!("0:0".parse(), Ok(FileID::from_raw_parts(0, 0).unwrap()));
+		Ok(())
+	}
+
+	#[test]
+	fn check_storages() -> std::io::Result<()> {
+		assert!(crate::same_storage("Cargo.toml", "src")?);
+		assert!(crate::same_storage_for_destination("Cargo.toml", "src/missing/dir/file")?);
+		assert!(crate::same_storage_for_destination("Cargo.toml", "missing")?);
+		#[cfg(unix)]
+		{
+			// The link itself is renamed, not the directory it points to, which is on another storage.
+			let dir = std::env::temp_dir().join(format!("fs-id-storages-{}", std::process::id()));
+			std::fs::create_dir_all(&dir)?;
+			let link = dir.join("link");
+			let result = std::os::unix::fs::symlink("/dev", &link)
+				.and_then(|()| crate::same_storage_for_destination(&link, &dir.join("renamed")));
+			std::fs::remove_dir_all(&dir)?;
+			assert!(result?);
+		}
+		Ok(())
+	}
+
+	#[test]
+	#[cfg(target_os = "linux")]
+	fn check_bind_mounts() -> std::io::Result<()> {
+		use crate::linux::MountedFileID;
+		// Mounting requires privileges, so the test uses the bind mounts that already exist, if there are any.
+		let mounts = crate::mounts::mounts()?;
+		for (i, mount) in mounts.iter().enumerate() {
+			for other in &mounts[i + 1..] {
+				if mount.storage_id() != other.storage_id() || mount.mount_point() == other.mount_point() {
+					continue;
+				}
+				let (path, other_path) = (mount.mount_point(), other.mount_point());
+				let ids = (MountedFileID::from_path(path), MountedFileID::from_path(other_path));
+				let (Ok(id), Ok(other_id)) = ids else {
+					continue;
+				};
+				// Either mount could be hidden by another one mounted over it.
+				if id.file_id().storage_id() == other_id.file_id().storage_id() && id.mount_id() != other_id.mount_id() {
+					assert!(crate::same_storage(path, other_path)?);
+					assert!(!crate::same_storage_for_destination(path, &other_path.join("file"))?);
+				}
+			}
+		}
+		Ok(())
+	}
+
+	#[test]
+	fn check_verified_open() -> std::io::Result<()> {
+		let expected = FileID::new("Cargo.toml")?;
+		crate::open_verified("Cargo.toml", expected)?;
+		let error = crate::open_verified("LICENSE", expected).unwrap_err();
+		let mismatch = error.get_ref().and_then(|error| error.downcast_ref::<crate::MismatchError>()).unwrap();
+		assert_eq!(mismatch.expected(), expected);
+		assert_eq!(mismatch.found(), FileID::new("LICENSE")?);
+		Ok(())
+	}
+
+	#[test]
+	fn check_sorting() -> std::io::Result<()> {
+		let sorted = crate::sort_paths_by_id(["LICENSE", "Cargo.toml", "src", "LICENSE"])?;
+		assert_eq!(sorted.len(), 4);
+		assert!(sorted.windows(2).all(|pair| pair[0].0 <= pair[1].0));
+		for (file_id, path) in sorted {
+			assert_eq!(file_id, FileID::new(path)?);
+		}
+		Ok(())
+	}
+
+	#[test]
+	#[cfg(unix)]
+	fn check_nofollow() -> std::io::Result<()> {
+		let link = std::env::temp_dir().join(format!("fs-id-nofollow-{}", std::process::id()));
+		std::os::unix::fs::symlink(std::fs::canonicalize("Cargo.toml")?, &link)?;
+		let link_id = FileID::new_nofollow(&link);
+		let target_id = FileID::new(link.as_path());
+		std::fs::remove_file(&link)?;
+		assert_ne!(link_id?, target_id?);
+		assert_eq!(FileID::new_nofollow("Cargo.toml")?, FileID::new("Cargo.toml")?);
+		Ok(())
+	}
 }
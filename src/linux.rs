@@ -0,0 +1,673 @@
+//! Linux-specific extensions to [`FileID`].
+
+use std::{
+	ffi::CString,
+	fs::{self, File, OpenOptions},
+	io,
+	mem,
+	os::{fd::{AsRawFd, FromRawFd, RawFd}, unix::{ffi::OsStrExt, fs::{MetadataExt, OpenOptionsExt}}},
+	path::Path
+};
+use crate::{FileID, GetID};
+
+// Not defined by every supported version of `libc`.
+const STATX_MNT_ID_UNIQUE: libc::c_uint = 0x4000;
+
+#[cfg(any(
+	target_arch = "mips",
+	target_arch = "mips64",
+	target_arch = "powerpc",
+	target_arch = "powerpc64",
+	target_arch = "sparc64"
+))]
+const IOC_READ: u32 = 2 << 29;
+#[cfg(not(any(
+	target_arch = "mips",
+	target_arch = "mips64",
+	target_arch = "powerpc",
+	target_arch = "powerpc64",
+	target_arch = "sparc64"
+)))]
+const IOC_READ: u32 = 2 << 30;
+
+/// `_IOR(0x15, 0, struct fsuuid2)`, also not defined by `libc`.
+const FS_IOC_GETFSUUID: u32 = IOC_READ | (mem::size_of::<FsUuid2>() as u32) << 16 | 0x15 << 8;
+
+#[repr(C)]
+struct FsUuid2 {
+	len: u8,
+	uuid: [u8; 16],
+}
+
+/// `_IOWR(0x94, 18, struct btrfs_ioctl_ino_lookup_args)`, also not defined by `libc`.
+/// The value is the same on every architecture, as the direction bits only differ in their layout.
+const BTRFS_IOC_INO_LOOKUP: u32 = 0xd000_9412;
+
+/// The inode number of the root directory of every btrfs subvolume.
+const BTRFS_FIRST_FREE_OBJECTID: u64 = 256;
+
+#[repr(C)]
+struct BtrfsInoLookupArgs {
+	treeid: u64,
+	objectid: u64,
+	name: [u8; 4080],
+}
+
+const MAX_HANDLE_SZ: usize = 128;
+
+/// `struct file_handle`, with enough space for the largest handle.
+#[repr(C)]
+struct RawFileHandle {
+	handle_bytes: libc::c_uint,
+	handle_type: libc::c_int,
+	f_handle: [u8; MAX_HANDLE_SZ],
+}
+
+// Also not defined by every supported version of `libc`.
+extern "C" {
+	fn name_to_handle_at(
+		dirfd: libc::c_int,
+		path: *const libc::c_char,
+		handle: *mut RawFileHandle,
+		mount_id: *mut libc::c_int,
+		flags: libc::c_int
+	) -> libc::c_int;
+	fn open_by_handle_at(mount_fd: libc::c_int, handle: *mut RawFileHandle, flags: libc::c_int) -> libc::c_int;
+}
+
+/// A [`FileID`] paired with the identifier of the mount it was reached through.
+/// 
+/// The same file can be reachable through multiple mounts (for example with bind mounts),
+/// in which case all of them share the same `FileID`.
+/// Two `MountedFileID`s instead only compare equal if both the file and the mount are the same.
+/// 
+/// # Examples
+/// 
+/// ```rust,no_run
+/// use fs_id::linux::MountedFileID;
+/// 
+/// fn main() -> std::io::Result<()> {
+///     let id1 = MountedFileID::from_path("/some/file/path.txt")?;
+///     let id2 = MountedFileID::from_path("/some/bind/mount/file/path.txt")?;
+///     assert_eq!(id1.file_id(), id2.file_id());
+///     assert_ne!(id1.mount_id(), id2.mount_id());
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MountedFileID {
+	file_id: FileID,
+	mount_id: u64,
+	unique: bool,
+	device: (u32, u32),
+}
+
+impl MountedFileID {
+	/// Obtains the identifier of an open file and of the mount it belongs to.
+	/// 
+	/// This function uses `statx` with `STATX_MNT_ID_UNIQUE`,
+	/// falling back to `STATX_MNT_ID` on kernels older than 6.8.
+	/// 
+	/// # Errors
+	/// 
+	/// This function will error if it fails to obtain the metadata containing the identifiers,
+	/// or if the kernel is too old to report mount identifiers (older than 5.8).
+	pub fn new<T: AsRawFd + ?Sized>(file: &T) -> io::Result<Self> {
+		statx(file.as_raw_fd(), b"\0".as_ptr().cast(), libc::AT_EMPTY_PATH)
+	}
+
+	/// Obtains the identifier of a path and of the mount it belongs to.
+	/// 
+	/// Like [`FileID::new`], only search permission on the path's parent directories is required.
+	/// 
+	/// # Errors
+	/// 
+	/// This function will error if the path does not exist,
+	/// if it fails to obtain the metadata containing the identifiers,
+	/// or if the kernel is too old to report mount identifiers (older than 5.8).
+	pub fn from_path<P: AsRef<Path> + ?Sized>(path: &P) -> io::Result<Self> {
+		let path = CString::new(path.as_ref().as_os_str().as_bytes())?;
+		statx(libc::AT_FDCWD, path.as_ptr(), 0)
+	}
+
+	/// Obtains the identifier of a path and of the mount it belongs to, without following symbolic links.
+	pub(crate) fn from_path_nofollow(path: &Path) -> io::Result<Self> {
+		let path = CString::new(path.as_os_str().as_bytes())?;
+		statx(libc::AT_FDCWD, path.as_ptr(), libc::AT_SYMLINK_NOFOLLOW)
+	}
+
+	/// Returns the identifier of the file, without the mount information.
+	#[must_use]
+	pub const fn file_id(&self) -> FileID {
+		self.file_id
+	}
+
+	/// Returns the identifier of the mount the file was reached through.
+	/// 
+	/// If [`MountedFileID::is_unique_mount_id`] returns `true`,
+	/// this is the 64 bit mount identifier that is never reused while the system is running,
+	/// otherwise it is the legacy mount identifier shown in `/proc/self/mountinfo`,
+	/// which can be reused after the mount is removed.
+	#[must_use]
+	pub const fn mount_id(&self) -> u64 {
+		self.mount_id
+	}
+
+	/// Returns `true` if [`MountedFileID::mount_id`] returns the unique mount identifier.
+	#[must_use]
+	pub const fn is_unique_mount_id(&self) -> bool {
+		self.unique
+	}
+
+	/// Returns the major and minor numbers of the device containing the file,
+	/// which together make up [`FileID::storage_id`].
+	#[must_use]
+	pub const fn device_numbers(&self) -> (u32, u32) {
+		self.device
+	}
+}
+
+impl GetID for MountedFileID {
+	/// Returns the identifier of the file, see [`MountedFileID::file_id`].
+	fn get_id(&self) -> io::Result<FileID> {
+		Ok(self.file_id)
+	}
+}
+
+/// A [`FileID`] paired with the generation number of the file's inode.
+/// 
+/// When a file is deleted its internal file identifier can be reused for a new, unrelated file,
+/// which would then have the same `FileID` as the deleted one.  
+/// Filesystems that reuse inodes increase the inode's generation number every time it is reused,
+/// so two `GenerationalFileID`s only compare equal if they identify the same file,
+/// even if the older one was obtained before the inode was reused.
+/// 
+/// Generation numbers are supported by filesystems such as ext4, btrfs and XFS,
+/// but not by every filesystem (for example tmpfs).
+/// 
+/// # Examples
+/// 
+/// ```rust,no_run
+/// use fs_id::linux::GenerationalFileID;
+/// 
+/// fn main() -> std::io::Result<()> {
+///     let old_id = GenerationalFileID::from_path("/some/file/path.txt")?;
+///     std::fs::remove_file("/some/file/path.txt")?;
+///     std::fs::write("/some/file/path.txt", "new file")?;
+///     let new_id = GenerationalFileID::from_path("/some/file/path.txt")?;
+///     assert_ne!(old_id, new_id);
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct GenerationalFileID {
+	file_id: FileID,
+	generation: u32,
+}
+
+impl GenerationalFileID {
+	/// Obtains the identifier and generation number of an open file.
+	/// 
+	/// This function uses `fstat64` and the `FS_IOC_GETVERSION` ioctl.
+	/// 
+	/// # Errors
+	/// 
+	/// This function will error if it fails to obtain the identifier or the generation number,
+	/// which includes when the filesystem does not support generation numbers (`ENOTTY`).
+	pub fn new<T: AsRawFd + ?Sized>(file: &T) -> io::Result<Self> {
+		let fd = file.as_raw_fd();
+		let file_id = crate::sys::get_fd_id(fd)?;
+		// The kernel writes an `int`, even though the ioctl is declared as taking a `long`.
+		let mut generation = [0u8; mem::size_of::<libc::c_long>()];
+		unsafe {
+			if libc::ioctl(fd, libc::FS_IOC_GETVERSION, generation.as_mut_ptr()) != 0 {
+				return Err(io::Error::last_os_error());
+			}
+		}
+		Ok(Self {
+			file_id,
+			generation: u32::from_ne_bytes(generation[..4].try_into().unwrap()),
+		})
+	}
+
+	/// Obtains the identifier and generation number of a path.
+	/// 
+	/// Unlike [`FileID::new`], the path is opened for reading, as the ioctl requires an open file.
+	/// 
+	/// # Errors
+	/// 
+	/// This function will error if it fails to open the path,
+	/// or in any of the cases described in [`GenerationalFileID::new`].
+	pub fn from_path<P: AsRef<Path> + ?Sized>(path: &P) -> io::Result<Self> {
+		Self::new(&File::open(path)?)
+	}
+
+	/// Returns the identifier of the file, without the generation number.
+	#[must_use]
+	pub const fn file_id(&self) -> FileID {
+		self.file_id
+	}
+
+	/// Returns the generation number of the file's inode.
+	#[must_use]
+	pub const fn generation(&self) -> u32 {
+		self.generation
+	}
+}
+
+impl GetID for GenerationalFileID {
+	/// Returns the identifier of the file, see [`GenerationalFileID::file_id`].
+	fn get_id(&self) -> io::Result<FileID> {
+		Ok(self.file_id)
+	}
+}
+
+/// A file identifier that remains the same across reboots.
+/// 
+/// [`FileID::storage_id`] is the device number of the storage containing the file,
+/// which is assigned when the storage is mounted and can change across reboots,
+/// or when removable drives are plugged in a different order.  
+/// `StableFileID` replaces it with the UUID of the filesystem, which only changes when the filesystem is recreated,
+/// making it suitable to be stored and compared with identifiers obtained after a reboot.
+/// 
+/// On btrfs every subvolume has its own inode numbers but they all share the filesystem's UUID,
+/// so the identifier of the subvolume containing the file is stored too, see [`StableFileID::subvolume_id`].
+/// 
+/// # Examples
+/// 
+/// ```rust,no_run
+/// use fs_id::linux::StableFileID;
+/// 
+/// fn main() -> std::io::Result<()> {
+///     let file_id = StableFileID::from_path("/some/file/path.txt")?;
+///     println!("{:x?} {}", file_id.fs_uuid(), file_id.internal_file_id());
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct StableFileID {
+	fs_uuid: [u8; 16],
+	fs_uuid_len: u8,
+	subvolume_id: Option<u64>,
+	internal_file_id: u64,
+}
+
+impl StableFileID {
+	/// Obtains the stable identifier of an open file.
+	/// 
+	/// The filesystem's UUID is obtained with the `FS_IOC_GETFSUUID` ioctl,
+	/// and on kernels older than 6.5 or filesystems that do not support it,
+	/// by looking for the storage's device in `/dev/disk/by-uuid`.  
+	/// On btrfs the subvolume is obtained with the `BTRFS_IOC_INO_LOOKUP` ioctl.
+	/// 
+	/// # Errors
+	/// 
+	/// This function will error if it fails to obtain the metadata containing the identifier,
+	/// if the UUID of the filesystem cannot be found (for example because it does not have one),
+	/// or if it fails to obtain the subvolume of a file on btrfs.
+	pub fn new<T: AsRawFd + ?Sized>(file: &T) -> io::Result<Self> {
+		let fd = file.as_raw_fd();
+		let file_id = crate::sys::get_fd_id(fd)?;
+		let (fs_uuid, fs_uuid_len) = unsafe {
+			let mut buf = FsUuid2 { len: 0, uuid: [0; 16] };
+			// Filesystems without a UUID report a nil one.
+			if libc::ioctl(fd, FS_IOC_GETFSUUID as _, &mut buf) == 0 && buf.uuid.iter().any(|&b| b != 0) {
+				(buf.uuid, buf.len.min(16))
+			} else {
+				uuid_from_disk_by_uuid(file_id.storage_id())?
+			}
+		};
+		Ok(Self {
+			fs_uuid,
+			fs_uuid_len,
+			subvolume_id: btrfs_subvolume_id(fd)?,
+			internal_file_id: file_id.0.1,
+		})
+	}
+
+	/// Obtains the stable identifier of a path.
+	/// 
+	/// Unlike [`FileID::new`], the path is opened for reading, as the ioctl requires an open file.
+	/// 
+	/// # Errors
+	/// 
+	/// This function will error if it fails to open the path,
+	/// or in any of the cases described in [`StableFileID::new`].
+	pub fn from_path<P: AsRef<Path> + ?Sized>(path: &P) -> io::Result<Self> {
+		Self::new(&File::open(path)?)
+	}
+
+	/// Returns the UUID of the filesystem containing the file.
+	/// 
+	/// The UUID is usually 16 bytes long, but some filesystems use shorter ones
+	/// (for example FAT uses a 4 bytes volume serial number).
+	#[must_use]
+	pub fn fs_uuid(&self) -> &[u8] {
+		&self.fs_uuid[..self.fs_uuid_len as usize]
+	}
+
+	/// Returns the identifier of the btrfs subvolume containing the file,
+	/// or `None` if the file is not on btrfs.
+	/// 
+	/// Unlike the device number of the subvolume, its identifier is stored in the filesystem
+	/// and does not change across reboots.
+	#[must_use]
+	pub const fn subvolume_id(&self) -> Option<u64> {
+		self.subvolume_id
+	}
+
+	/// Returns the internal file identifier, see [`FileID::internal_file_id`].
+	#[must_use]
+	pub const fn internal_file_id(&self) -> u128 {
+		self.internal_file_id as u128
+	}
+}
+
+/// Returns the identifier of the btrfs subvolume containing an open file, or `None` if it is not on btrfs.
+fn btrfs_subvolume_id(fd: RawFd) -> io::Result<Option<u64>> {
+	unsafe {
+		let mut statfs: libc::statfs = mem::zeroed();
+		if libc::fstatfs(fd, &mut statfs) != 0 {
+			return Err(io::Error::last_os_error());
+		}
+		#[allow(clippy::unnecessary_cast)]
+		if statfs.f_type as libc::c_long != libc::BTRFS_SUPER_MAGIC as libc::c_long {
+			return Ok(None);
+		}
+		// Looking up the root directory of the file's own subvolume (`treeid` 0) only returns its identifier,
+		// and unlike other lookups does not require `CAP_SYS_ADMIN`.
+		let mut args = BtrfsInoLookupArgs { treeid: 0, objectid: BTRFS_FIRST_FREE_OBJECTID, name: [0; 4080] };
+		if libc::ioctl(fd, BTRFS_IOC_INO_LOOKUP as _, &mut args) != 0 {
+			return Err(io::Error::last_os_error());
+		}
+		Ok(Some(args.treeid))
+	}
+}
+
+/// A handle that can be used to reopen a file without its path, even after it was renamed or moved.
+/// 
+/// Handles are obtained with `name_to_handle_at` and reopened with `open_by_handle_at`,
+/// which requires the `CAP_DAC_READ_SEARCH` capability.  
+/// They remain valid as long as the file exists, but only within the filesystem they were obtained on,
+/// and not every filesystem supports them.
+/// 
+/// # Examples
+/// 
+/// ```rust,no_run
+/// use std::fs::File;
+/// use fs_id::{linux::FileHandle, FileID};
+/// 
+/// fn main() -> std::io::Result<()> {
+///     let handle = FileHandle::from_path("/home/user/file.txt")?;
+///     std::fs::rename("/home/user/file.txt", "/home/user/renamed.txt")?;
+///     let file = handle.open(&File::open("/home")?)?;
+///     assert_eq!(FileID::new(&file)?, handle.file_id());
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FileHandle {
+	handle_type: i32,
+	bytes: Vec<u8>,
+	mount_id: i32,
+	file_id: FileID,
+}
+
+impl FileHandle {
+	/// Obtains the handle of an open file.
+	/// 
+	/// # Errors
+	/// 
+	/// This function will error if it fails to obtain the handle or the identifier of the file,
+	/// which includes when the filesystem does not support handles (`EOPNOTSUPP`).
+	pub fn new<T: AsRawFd + ?Sized>(file: &T) -> io::Result<Self> {
+		let fd = file.as_raw_fd();
+		let mut raw = RawFileHandle {
+			handle_bytes: MAX_HANDLE_SZ as libc::c_uint,
+			handle_type: 0,
+			f_handle: [0; MAX_HANDLE_SZ],
+		};
+		let mut mount_id = 0;
+		unsafe {
+			if name_to_handle_at(fd, b"\0".as_ptr().cast(), &mut raw, &mut mount_id, libc::AT_EMPTY_PATH) != 0 {
+				return Err(io::Error::last_os_error());
+			}
+		}
+		Ok(Self {
+			handle_type: raw.handle_type,
+			bytes: raw.f_handle[..raw.handle_bytes as usize].to_vec(),
+			mount_id,
+			file_id: crate::sys::get_fd_id(fd)?,
+		})
+	}
+
+	/// Obtains the handle of a path.
+	/// 
+	/// Like [`FileID::new`], only search permission on the path's parent directories is required,
+	/// as the path is opened with `O_PATH`.
+	/// 
+	/// # Errors
+	/// 
+	/// This function will error if the path does not exist,
+	/// or in any of the cases described in [`FileHandle::new`].
+	pub fn from_path<P: AsRef<Path> + ?Sized>(path: &P) -> io::Result<Self> {
+		Self::new(&OpenOptions::new().read(true).custom_flags(libc::O_PATH).open(path)?)
+	}
+
+	/// Creates a handle from its parts, as returned by [`FileHandle::handle_type`], [`FileHandle::bytes`],
+	/// [`FileHandle::mount_id`] and [`FileHandle::file_id`].
+	/// 
+	/// Returns `None` if the handle is larger than the kernel allows.
+	#[must_use]
+	pub fn from_raw_parts(handle_type: i32, bytes: Vec<u8>, mount_id: i32, file_id: FileID) -> Option<Self> {
+		(bytes.len() <= MAX_HANDLE_SZ).then_some(Self { handle_type, bytes, mount_id, file_id })
+	}
+
+	/// Reopens the file for reading.
+	/// 
+	/// `mount` can be any open file or directory within the same filesystem as the file,
+	/// such as its mount point.
+	/// 
+	/// # Errors
+	/// 
+	/// This function will error if the process lacks the `CAP_DAC_READ_SEARCH` capability (`EPERM`),
+	/// if the file no longer exists (`ESTALE`), or if it fails to open it.
+	pub fn open<T: AsRawFd + ?Sized>(&self, mount: &T) -> io::Result<File> {
+		self.open_with_flags(mount, libc::O_RDONLY)
+	}
+
+	/// Reopens the file with the given `open(2)` flags, see [`FileHandle::open`].
+	/// 
+	/// `O_CLOEXEC` is always added to the flags.
+	/// 
+	/// # Errors
+	/// 
+	/// See [`FileHandle::open`].
+	pub fn open_with_flags<T: AsRawFd + ?Sized>(&self, mount: &T, flags: i32) -> io::Result<File> {
+		let mut raw = RawFileHandle {
+			handle_bytes: self.bytes.len() as libc::c_uint,
+			handle_type: self.handle_type,
+			f_handle: [0; MAX_HANDLE_SZ],
+		};
+		raw.f_handle[..self.bytes.len()].copy_from_slice(&self.bytes);
+		unsafe {
+			let fd = open_by_handle_at(mount.as_raw_fd(), &mut raw, flags | libc::O_CLOEXEC);
+			if fd < 0 {
+				Err(io::Error::last_os_error())
+			} else {
+				Ok(File::from_raw_fd(fd))
+			}
+		}
+	}
+
+	/// Returns the type of the handle, which is specific to the filesystem.
+	#[must_use]
+	pub const fn handle_type(&self) -> i32 {
+		self.handle_type
+	}
+
+	/// Returns the opaque bytes of the handle.
+	#[must_use]
+	pub fn bytes(&self) -> &[u8] {
+		&self.bytes
+	}
+
+	/// Returns the legacy identifier of the mount the handle was obtained through,
+	/// see [`MountedFileID::mount_id`].
+	#[must_use]
+	pub const fn mount_id(&self) -> i32 {
+		self.mount_id
+	}
+
+	/// Returns the identifier of the file.
+	#[must_use]
+	pub const fn file_id(&self) -> FileID {
+		self.file_id
+	}
+}
+
+impl GetID for FileHandle {
+	/// Returns the identifier of the file, see [`FileHandle::file_id`].
+	fn get_id(&self) -> io::Result<FileID> {
+		Ok(self.file_id)
+	}
+}
+
+fn uuid_from_disk_by_uuid(storage_id: u64) -> io::Result<([u8; 16], u8)> {
+	let not_found = || io::Error::new(io::ErrorKind::NotFound, "could not find the UUID of the filesystem");
+	let entries = match fs::read_dir("/dev/disk/by-uuid") {
+		Ok(entries) => entries,
+		Err(error) if error.kind() == io::ErrorKind::NotFound => return Err(not_found()),
+		Err(error) => return Err(error),
+	};
+	for entry in entries {
+		let entry = entry?;
+		let Ok(metadata) = fs::metadata(entry.path()) else {
+			continue;
+		};
+		if metadata.rdev() != storage_id {
+			continue;
+		}
+		// The names are the UUIDs in hexadecimal, with some dashes in between.
+		let name = entry.file_name();
+		let digits: Vec<u8> = name.as_bytes().iter().copied().filter(|&c| c != b'-').collect();
+		let mut uuid = [0; 16];
+		if digits.len() % 2 != 0 || digits.len() > 32 {
+			continue;
+		}
+		let mut valid = true;
+		for (byte, pair) in uuid.iter_mut().zip(digits.chunks(2)) {
+			match std::str::from_utf8(pair).ok().and_then(|pair| u8::from_str_radix(pair, 16).ok()) {
+				Some(value) => *byte = value,
+				None => valid = false,
+			}
+		}
+		if valid {
+			return Ok((uuid, (digits.len() / 2) as u8));
+		}
+	}
+	Err(not_found())
+}
+
+fn statx(dirfd: libc::c_int, path: *const libc::c_char, flags: libc::c_int) -> io::Result<MountedFileID> {
+	unsafe {
+		let mut buf: libc::statx = mem::zeroed();
+		if libc::statx(
+			dirfd,
+			path,
+			flags,
+			libc::STATX_INO | STATX_MNT_ID_UNIQUE | libc::STATX_MNT_ID,
+			&mut buf
+		) != 0 {
+			return Err(io::Error::last_os_error());
+		}
+		let unique = buf.stx_mask & STATX_MNT_ID_UNIQUE != 0;
+		if !unique && buf.stx_mask & libc::STATX_MNT_ID == 0 {
+			return Err(io::Error::new(io::ErrorKind::Unsupported, "the kernel does not report mount identifiers"));
+		}
+		Ok(MountedFileID {
+			file_id: FileID((libc::makedev(buf.stx_dev_major, buf.stx_dev_minor), buf.stx_ino)),
+			mount_id: buf.stx_mnt_id,
+			unique,
+			device: (buf.stx_dev_major, buf.stx_dev_minor),
+		})
+	}
+}
+
+/// Returns the identifier of a file and the legacy identifier of its mount, as shown in `/proc/self/mountinfo`,
+/// or `None` as the mount identifier on kernels older than 5.8.
+pub(crate) fn legacy_mount_id(
+	dirfd: libc::c_int,
+	path: *const libc::c_char,
+	flags: libc::c_int
+) -> io::Result<(FileID, Option<u64>)> {
+	unsafe {
+		let mut buf: libc::statx = mem::zeroed();
+		if libc::statx(dirfd, path, flags, libc::STATX_INO | libc::STATX_MNT_ID, &mut buf) != 0 {
+			return Err(io::Error::last_os_error());
+		}
+		let file_id = FileID((libc::makedev(buf.stx_dev_major, buf.stx_dev_minor), buf.stx_ino));
+		Ok((file_id, (buf.stx_mask & libc::STATX_MNT_ID != 0).then_some(buf.stx_mnt_id)))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::FileID;
+	use super::{FileHandle, GenerationalFileID, MountedFileID, StableFileID};
+
+	#[test]
+	fn check_mount_ids() -> std::io::Result<()> {
+		let id1 = MountedFileID::from_path("Cargo.toml")?;
+		let id2 = MountedFileID::new(&std::fs::File::open("Cargo.toml")?)?;
+		assert_eq!(id1, id2);
+		assert_eq!(id1.file_id(), FileID::new("Cargo.toml")?);
+		Ok(())
+	}
+
+	#[test]
+	fn check_generations() -> std::io::Result<()> {
+		match GenerationalFileID::from_path("Cargo.toml") {
+			Ok(id) => assert_eq!(id, GenerationalFileID::from_path("Cargo.toml")?),
+			// The filesystem the tests are running on might not support generation numbers.
+			Err(error) if error.raw_os_error() == Some(libc::ENOTTY) => {},
+			Err(error) => return Err(error),
+		}
+		Ok(())
+	}
+
+	#[test]
+	fn check_stable_ids() -> std::io::Result<()> {
+		match StableFileID::from_path("Cargo.toml") {
+			Ok(id) => {
+				assert_eq!(id, StableFileID::from_path("Cargo.toml")?);
+				assert_eq!(id.internal_file_id(), FileID::new("Cargo.toml")?.internal_file_id());
+				assert_eq!(id.subvolume_id().is_some(), crate::mounts::storage_info("Cargo.toml")?
+					.map_or(false, |info| info.fs_type() == "btrfs"));
+			},
+			// The filesystem the tests are running on might not have a UUID.
+			Err(error) if error.kind() == std::io::ErrorKind::NotFound => {},
+			Err(error) => return Err(error),
+		}
+		Ok(())
+	}
+
+	#[test]
+	fn check_file_handles() -> std::io::Result<()> {
+		let handle = match FileHandle::from_path("Cargo.toml") {
+			Ok(handle) => handle,
+			// The filesystem the tests are running on might not support handles.
+			Err(error) if error.raw_os_error() == Some(libc::EOPNOTSUPP) => return Ok(()),
+			Err(error) => return Err(error),
+		};
+		assert_eq!(handle, FileHandle::new(&std::fs::File::open("Cargo.toml")?)?);
+		assert_eq!(handle.file_id(), FileID::new("Cargo.toml")?);
+		match handle.open(&std::fs::File::open(".")?) {
+			Ok(file) => assert_eq!(FileID::new(&file)?, handle.file_id()),
+			// Reopening requires `CAP_DAC_READ_SEARCH`.
+			Err(error) if error.raw_os_error() == Some(libc::EPERM) => {},
+			Err(error) => return Err(error),
+		}
+		Ok(())
+	}
+}
@@ -0,0 +1,132 @@
+use std::{env, io, path::PathBuf, process::ExitCode};
+use fs_id::{compare_ids, du::DiskUsage, walk::{find_paths, Walker}, FileID};
+
+const USAGE: &str = "\
+Usage: fs-id <command> [arguments]
+
+Commands:
+  show [--no-follow] <paths>...   Print the identifier of each path
+  same <path1> <path2>            Exit with 0 if both paths are the same file, 1 otherwise
+  group <directory>               List the files with multiple hard links inside the directory
+  du [-x] <directories>...        Print the disk usage of each directory, counting hard links once
+                                  (-x skips other filesystems mounted inside the directories)
+  find <id> <directories>...      Print every path inside the directories with the given identifier
+
+Identifiers are printed as `<storage id>:<internal file id>`, both in hexadecimal.";
+
+fn main() -> ExitCode {
+	let args: Vec<String> = env::args().skip(1).collect();
+	let Some((command, args)) = args.split_first() else {
+		eprintln!("{USAGE}");
+		return ExitCode::from(2);
+	};
+	let result = match command.as_str() {
+		"show" => show(args),
+		"same" => same(args),
+		"group" => group(args),
+		"du" => du(args),
+		"find" => find(args),
+		"help" | "-h" | "--help" => {
+			println!("{USAGE}");
+			Ok(ExitCode::SUCCESS)
+		}
+		_ => Err(usage_error(format!("unknown command `{command}`"))),
+	};
+	result.unwrap_or_else(|error| {
+		eprintln!("fs-id: {error}");
+		ExitCode::from(2)
+	})
+}
+
+fn usage_error(message: String) -> io::Error {
+	io::Error::new(io::ErrorKind::InvalidInput, format!("{message}\n\n{USAGE}"))
+}
+
+fn with_context(error: io::Error, path: &str) -> io::Error {
+	io::Error::new(error.kind(), format!("{path}: {error}"))
+}
+
+fn show(args: &[String]) -> io::Result<ExitCode> {
+	let (follow, paths) = match args.split_first() {
+		Some((flag, paths)) if flag == "--no-follow" => (false, paths),
+		_ => (true, args),
+	};
+	if paths.is_empty() {
+		return Err(usage_error("`show` requires at least one path".to_owned()));
+	}
+	for path in paths {
+		let id = if follow { FileID::new(path.as_str()) } else { FileID::new_nofollow(path) };
+		println!("{}\t{path}", id.map_err(|error| with_context(error, path))?);
+	}
+	Ok(ExitCode::SUCCESS)
+}
+
+fn same(args: &[String]) -> io::Result<ExitCode> {
+	let [path1, path2] = args else {
+		return Err(usage_error("`same` requires exactly two paths".to_owned()));
+	};
+	if compare_ids(path1.as_str(), path2.as_str())? {
+		Ok(ExitCode::SUCCESS)
+	} else {
+		Ok(ExitCode::FAILURE)
+	}
+}
+
+fn group(args: &[String]) -> io::Result<ExitCode> {
+	let [dir] = args else {
+		return Err(usage_error("`group` requires exactly one directory".to_owned()));
+	};
+	// Like `du`, unreadable entries are reported and skipped, and the exit code tells if any were.
+	let mut code = ExitCode::SUCCESS;
+	let files = Walker::new(dir).group_by_id_lossy(|error| {
+		eprintln!("fs-id: {error}");
+		code = ExitCode::FAILURE;
+	});
+	let mut groups: Vec<(&FileID, Vec<&PathBuf>)> = files
+		.iter_with_paths()
+		.filter(|(_, _, paths)| paths.len() > 1)
+		.map(|(id, _, paths)| (id, paths.iter().collect()))
+		.collect();
+	groups.sort();
+	for (id, paths) in groups {
+		println!("{id}");
+		for path in paths {
+			println!("\t{}", path.display());
+		}
+	}
+	Ok(code)
+}
+
+fn du(args: &[String]) -> io::Result<ExitCode> {
+	let (same_storage, dirs) = match args.split_first() {
+		Some((flag, dirs)) if flag == "-x" => (true, dirs),
+		_ => (false, args),
+	};
+	if dirs.is_empty() {
+		return Err(usage_error("`du` requires at least one directory".to_owned()));
+	}
+	// Like `du`, unreadable entries are reported and skipped, and the exit code tells if any were.
+	let mut code = ExitCode::SUCCESS;
+	for dir in dirs {
+		let usage = DiskUsage::from_walker_lossy(Walker::new(dir).same_storage(same_storage), |error| {
+			eprintln!("fs-id: {error}");
+			code = ExitCode::FAILURE;
+		});
+		// The directory itself could not be read.
+		if usage.files() > 0 {
+			println!("{}\t{}\t{dir}", usage.disk_size(), usage.apparent_size());
+		}
+	}
+	Ok(code)
+}
+
+fn find(args: &[String]) -> io::Result<ExitCode> {
+	let Some((id, dirs)) = args.split_first().filter(|(_, dirs)| !dirs.is_empty()) else {
+		return Err(usage_error("`find` requires an identifier and at least one directory".to_owned()));
+	};
+	let id: FileID = id.parse().map_err(|error| usage_error(format!("{error}")))?;
+	for path in find_paths(id, dirs)? {
+		println!("{}", path.display());
+	}
+	Ok(ExitCode::SUCCESS)
+}
@@ -0,0 +1,260 @@
+//! Information about the storage identified by [`FileID::storage_id`](crate::FileID::storage_id), obtained from `/proc/self/mountinfo`.
+//! 
+//! # Examples
+//! 
+//! ```rust,no_run
+//! use fs_id::mounts::storage_info;
+//! 
+//! fn main() -> std::io::Result<()> {
+//!     if let Some(info) = storage_info("/home/user/file.txt")? {
+//!         // for example: "ext4 on /dev/nvme0n1p2 mounted at /home"
+//!         println!("{info}");
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+use std::{
+	ffi::{CString, OsString},
+	fmt,
+	fs,
+	io,
+	os::{fd::AsRawFd, unix::ffi::{OsStrExt, OsStringExt}},
+	path::{Path, PathBuf}
+};
+use crate::FileID;
+
+/// A mount, as described by a line of `/proc/self/mountinfo`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct StorageInfo {
+	mount_id: u32,
+	major: u32,
+	minor: u32,
+	root: PathBuf,
+	mount_point: PathBuf,
+	options: String,
+	fs_type: String,
+	source: String,
+	super_options: String,
+}
+
+impl StorageInfo {
+	/// Returns the legacy identifier of the mount, see [`MountedFileID::mount_id`](crate::linux::MountedFileID::mount_id).
+	#[must_use]
+	pub const fn mount_id(&self) -> u32 {
+		self.mount_id
+	}
+
+	/// Returns the major number of the device, see [`MountedFileID::device_numbers`](crate::linux::MountedFileID::device_numbers).
+	#[must_use]
+	pub const fn major(&self) -> u32 {
+		self.major
+	}
+
+	/// Returns the minor number of the device, see [`MountedFileID::device_numbers`](crate::linux::MountedFileID::device_numbers).
+	#[must_use]
+	pub const fn minor(&self) -> u32 {
+		self.minor
+	}
+
+	/// Returns the storage identifier of the files in this mount, see [`FileID::storage_id`](crate::FileID::storage_id).
+	#[must_use]
+	pub fn storage_id(&self) -> u64 {
+		libc::makedev(self.major, self.minor)
+	}
+
+	/// Returns the directory within the filesystem that is mounted,
+	/// which is `/` unless only part of the filesystem is mounted (for example with a bind mount).
+	#[must_use]
+	pub fn root(&self) -> &Path {
+		&self.root
+	}
+
+	/// Returns the path the filesystem is mounted at.
+	#[must_use]
+	pub fn mount_point(&self) -> &Path {
+		&self.mount_point
+	}
+
+	/// Returns the options of the mount, such as `rw,noatime`.
+	#[must_use]
+	pub fn options(&self) -> &str {
+		&self.options
+	}
+
+	/// Returns the type of the filesystem, such as `ext4`.
+	#[must_use]
+	pub fn fs_type(&self) -> &str {
+		&self.fs_type
+	}
+
+	/// Returns the source of the filesystem, usually the path of its device (such as `/dev/sda1`).
+	#[must_use]
+	pub fn source(&self) -> &str {
+		&self.source
+	}
+
+	/// Returns the options of the filesystem itself, shared by all of its mounts.
+	#[must_use]
+	pub fn super_options(&self) -> &str {
+		&self.super_options
+	}
+
+	fn parse(line: &str) -> Option<Self> {
+		let mut fields = line.split(' ');
+		let mount_id = fields.next()?.parse().ok()?;
+		let _parent_id = fields.next()?;
+		let (major, minor) = fields.next()?.split_once(':')?;
+		let root = unescape(fields.next()?).into();
+		let mount_point = unescape(fields.next()?).into();
+		let options = fields.next()?.to_owned();
+		// Skip the optional fields, which are terminated by a single dash.
+		fields.by_ref().find(|&field| field == "-")?;
+		Some(Self {
+			mount_id,
+			major: major.parse().ok()?,
+			minor: minor.parse().ok()?,
+			root,
+			mount_point,
+			options,
+			fs_type: unescape(fields.next()?).to_string_lossy().into_owned(),
+			source: unescape(fields.next()?).to_string_lossy().into_owned(),
+			super_options: fields.next()?.to_owned(),
+		})
+	}
+}
+
+impl fmt::Display for StorageInfo {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{} on {} mounted at {}", self.fs_type, self.source, self.mount_point.display())
+	}
+}
+
+/// Reverses the octal escapes (such as `\040` for spaces) used by `/proc/self/mountinfo`.
+fn unescape(field: &str) -> OsString {
+	let bytes = field.as_bytes();
+	let mut unescaped = Vec::with_capacity(bytes.len());
+	let mut i = 0;
+	while i < bytes.len() {
+		let escape = bytes.get(i + 1..i + 4)
+			.filter(|_| bytes[i] == b'\\')
+			.and_then(|digits| std::str::from_utf8(digits).ok())
+			.and_then(|digits| u8::from_str_radix(digits, 8).ok());
+		match escape {
+			Some(byte) => {
+				unescaped.push(byte);
+				i += 4;
+			}
+			None => {
+				unescaped.push(bytes[i]);
+				i += 1;
+			}
+		}
+	}
+	OsString::from_vec(unescaped)
+}
+
+/// Returns every mount visible to the current process, in the order they were mounted.
+/// 
+/// # Errors
+/// 
+/// Returns [`io::Error`] when failing to read `/proc/self/mountinfo`.
+pub fn mounts() -> io::Result<Vec<StorageInfo>> {
+	let mountinfo = fs::read_to_string("/proc/self/mountinfo")?;
+	Ok(mountinfo.lines().filter_map(StorageInfo::parse).collect())
+}
+
+/// Returns the information about the mount a path is reached through, or `None` if it cannot be found.
+/// 
+/// The mount is found by its identifier, obtained with `statx`,
+/// so unlike [`storage_info_for_id`] this also works on filesystems whose storage identifiers
+/// do not match the device numbers in `/proc/self/mountinfo` (such as btrfs).  
+/// On kernels older than 5.8, which do not report mount identifiers, this is the same as [`storage_info_for_id`].
+/// 
+/// # Errors
+/// 
+/// Returns [`io::Error`] when failing to obtain the identifiers of the path or to read `/proc/self/mountinfo`.
+pub fn storage_info<P: AsRef<Path> + ?Sized>(path: &P) -> io::Result<Option<StorageInfo>> {
+	let c_path = CString::new(path.as_ref().as_os_str().as_bytes())?;
+	match crate::linux::legacy_mount_id(libc::AT_FDCWD, c_path.as_ptr(), 0) {
+		Err(error) if error.raw_os_error() == Some(libc::ENOSYS) => storage_info_for_id(FileID::new(path.as_ref())?),
+		result => with_mount_id(result?),
+	}
+}
+
+/// Returns the information about the mount an open file was opened through, or `None` if it cannot be found.
+/// 
+/// See [`storage_info`] for more information.
+/// 
+/// # Errors
+/// 
+/// Returns [`io::Error`] when failing to obtain the identifiers of the file or to read `/proc/self/mountinfo`.
+pub fn storage_info_for_file<T: AsRawFd + ?Sized>(file: &T) -> io::Result<Option<StorageInfo>> {
+	let fd = file.as_raw_fd();
+	match crate::linux::legacy_mount_id(fd, b"\0".as_ptr().cast(), libc::AT_EMPTY_PATH) {
+		Err(error) if error.raw_os_error() == Some(libc::ENOSYS) => storage_info_for_id(crate::sys::get_fd_id(fd)?),
+		result => with_mount_id(result?),
+	}
+}
+
+fn with_mount_id((file_id, mount_id): (FileID, Option<u64>)) -> io::Result<Option<StorageInfo>> {
+	match mount_id {
+		Some(mount_id) => Ok(mounts()?.into_iter().find(|info| u64::from(info.mount_id) == mount_id)),
+		None => storage_info_for_id(file_id),
+	}
+}
+
+/// Returns the information about the storage containing a file, or `None` if it is not mounted anywhere.
+/// 
+/// The storage is found by its identifier (see [`FileID::storage_id`](crate::FileID::storage_id)),
+/// and if it is mounted multiple times the mount of its whole filesystem (with a [`StorageInfo::root`] of `/`)
+/// is preferred over the others.
+/// 
+/// Prefer [`storage_info`] or [`storage_info_for_file`] when the path or the file is available:
+/// on some filesystems (such as btrfs subvolumes) the storage identifiers of the files
+/// differ from the device numbers in `/proc/self/mountinfo`, so this function returns `None` for them.
+/// 
+/// # Errors
+/// 
+/// Returns [`io::Error`] when failing to read `/proc/self/mountinfo`.
+pub fn storage_info_for_id(id: FileID) -> io::Result<Option<StorageInfo>> {
+	let storage_id = id.storage_id();
+	let mut matching = mounts()?.into_iter().filter(|info| info.storage_id() == storage_id);
+	let first = matching.next();
+	if first.as_ref().map_or(true, |info| info.root == Path::new("/")) {
+		return Ok(first);
+	}
+	Ok(matching.find(|info| info.root == Path::new("/")).or(first))
+}
+
+#[cfg(test)]
+mod tests {
+	use std::{fs::File, path::Path};
+	use crate::FileID;
+	use super::{storage_info, storage_info_for_file, storage_info_for_id, StorageInfo};
+
+	#[test]
+	fn check_parsing() {
+		let info = StorageInfo::parse(
+			"36 35 98:0 /mnt1 /mnt/with\\040space rw,noatime master:1 shared:2 - ext3 /dev/root rw,errors=continue"
+		).unwrap();
+		assert_eq!((info.mount_id(), info.major(), info.minor()), (36, 98, 0));
+		assert_eq!(info.root(), Path::new("/mnt1"));
+		assert_eq!(info.mount_point(), Path::new("/mnt/with space"));
+		assert_eq!(info.options(), "rw,noatime");
+		assert_eq!(info.to_string(), "ext3 on /dev/root mounted at /mnt/with space");
+		assert_eq!(info.super_options(), "rw,errors=continue");
+	}
+
+	#[test]
+	fn check_storage_info() -> std::io::Result<()> {
+		let info = storage_info("Cargo.toml")?.unwrap();
+		assert!(std::env::current_dir()?.starts_with(info.mount_point()));
+		assert_eq!(storage_info_for_file(&File::open("Cargo.toml")?)?, Some(info.clone()));
+		// The storage identifier only matches on filesystems without per-subvolume device numbers.
+		if let Some(by_id) = storage_info_for_id(FileID::new("Cargo.toml")?)? {
+			assert_eq!(by_id.storage_id(), info.storage_id());
+		}
+		Ok(())
+	}
+}
@@ -0,0 +1,410 @@
+//! Identification of the files other processes have open, through `/proc/<pid>/fd`.
+//! 
+//! Reading the file descriptors of a process owned by another user usually requires root privileges
+//! (more precisely, the permission to `ptrace` the process).
+//! 
+//! # Examples
+//! 
+//! ```rust,no_run
+//! use fs_id::{process::fds, FileID};
+//! 
+//! fn main() -> std::io::Result<()> {
+//!     let file_id = FileID::new("/some/file/path.txt")?;
+//!     for fd in fds(1234)? {
+//!         let (fd, id, _kind) = fd?;
+//!         if id == file_id {
+//!             println!("process 1234 has the file open as {fd}");
+//!         }
+//!     }
+//!     Ok(())
+//! }
+//! ```
+//! 
+//! To find every process using a file, see [`holders`],
+//! and to find every process using a storage (for example before unmounting it), see [`storage_holders`].
+
+use std::{collections::HashSet, fs::{self, ReadDir}, io, os::{fd::RawFd, unix::fs::{FileTypeExt, MetadataExt}}, path::{Path, PathBuf}};
+use crate::FileID;
+
+/// The kind of file a file descriptor refers to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum FdKind {
+	/// A regular file.
+	File,
+	/// A directory.
+	Directory,
+	/// A pipe or FIFO.
+	Pipe,
+	/// A socket.
+	Socket,
+	/// A file without a path, such as an `eventfd`, `epoll` or `signalfd`.
+	/// Most of these share the same identifier.
+	AnonInode,
+	/// Anything else, such as devices.
+	Other,
+}
+
+impl FdKind {
+	fn of(path: &Path, metadata: &fs::Metadata) -> Self {
+		// Anonymous inodes do not always have a file type, so they are recognized by the link target.
+		let is_anon_inode = fs::read_link(path)
+			.map_or(false, |target| target.as_os_str().to_string_lossy().starts_with("anon_inode:"));
+		let file_type = metadata.file_type();
+		if is_anon_inode {
+			Self::AnonInode
+		} else if file_type.is_file() {
+			Self::File
+		} else if file_type.is_dir() {
+			Self::Directory
+		} else if file_type.is_fifo() {
+			Self::Pipe
+		} else if file_type.is_socket() {
+			Self::Socket
+		} else {
+			Self::Other
+		}
+	}
+}
+
+impl FileID {
+	/// Obtains the identifier of the file a file descriptor of another process refers to.
+	/// 
+	/// The identifier is obtained by following the magic link `/proc/<pid>/fd/<fd>`,
+	/// so it works even if the file was deleted or is not reachable from the current process.
+	/// 
+	/// # Errors
+	/// 
+	/// This function will error if the process or the file descriptor do not exist,
+	/// or if the current process is not allowed to inspect the other one.
+	/// 
+	/// # Examples
+	/// 
+	/// ```rust,no_run
+	/// use fs_id::FileID;
+	/// 
+	/// fn main() -> std::io::Result<()> {
+	///     let stdin_id = FileID::of_process_fd(1234, 0)?;
+	///     println!("{}", stdin_id == FileID::new("/dev/null")?);
+	///     Ok(())
+	/// }
+	/// ```
+	pub fn of_process_fd(pid: u32, fd: RawFd) -> io::Result<Self> {
+		Self::new(fd_dir(pid).join(fd.to_string()).as_path())
+	}
+}
+
+/// An iterator over the open file descriptors of a process, see [`fds`].
+#[derive(Debug)]
+pub struct Fds {
+	entries: ReadDir,
+}
+
+impl Iterator for Fds {
+	type Item = io::Result<(RawFd, FileID, FdKind)>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		loop {
+			let entry = match self.entries.next()? {
+				Ok(entry) => entry,
+				Err(error) => return Some(Err(error)),
+			};
+			let Some(fd) = entry.file_name().to_str().and_then(|name| name.parse().ok()) else {
+				continue;
+			};
+			let path = entry.path();
+			match fs::metadata(&path) {
+				Ok(metadata) => {
+					let id = FileID((metadata.dev(), metadata.ino()));
+					return Some(Ok((fd, id, FdKind::of(&path, &metadata))));
+				}
+				// The file descriptor was closed after the directory was read.
+				Err(error) if error.kind() == io::ErrorKind::NotFound => continue,
+				Err(error) => return Some(Err(error)),
+			}
+		}
+	}
+}
+
+/// Returns an iterator over the open file descriptors of a process, with the identifiers and kinds of their files.
+/// 
+/// File descriptors closed while iterating are skipped.
+/// 
+/// # Errors
+/// 
+/// Returns [`io::Error`] if the process does not exist,
+/// or if the current process is not allowed to inspect the other one.
+/// The iterator itself yields an error when failing to obtain the identifier of a file descriptor.
+pub fn fds(pid: u32) -> io::Result<Fds> {
+	Ok(Fds { entries: fs::read_dir(fd_dir(pid))? })
+}
+
+fn fd_dir(pid: u32) -> PathBuf {
+	Path::new("/proc").join(pid.to_string()).join("fd")
+}
+
+/// How a process is using a file, see [`Holder`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Mode {
+	/// The file is open for reading only (or with `O_PATH`).
+	Read,
+	/// The file is open for writing only.
+	Write,
+	/// The file is open for both reading and writing.
+	ReadWrite,
+	/// The file is mapped in the memory of the process, such as a shared library.
+	Mapped,
+	/// The file is the current working directory of the process.
+	Cwd,
+	/// The file is the root directory of the process (see `chroot`).
+	Root,
+	/// The file is the executable of the process.
+	Exe,
+}
+
+/// A process using a file, see [`holders`] and [`storage_holders`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Holder {
+	pid: u32,
+	id: FileID,
+	fd: Option<RawFd>,
+	mode: Mode,
+}
+
+impl Holder {
+	/// Returns the identifier of the process.
+	#[must_use]
+	pub const fn pid(&self) -> u32 {
+		self.pid
+	}
+
+	/// Returns the identifier of the file the process is using.
+	#[must_use]
+	pub const fn id(&self) -> FileID {
+		self.id
+	}
+
+	/// Returns the file descriptor the process has the file open as,
+	/// or `None` if the file is not used through a file descriptor.
+	#[must_use]
+	pub const fn fd(&self) -> Option<RawFd> {
+		self.fd
+	}
+
+	/// Returns how the process is using the file.
+	#[must_use]
+	pub const fn mode(&self) -> Mode {
+		self.mode
+	}
+}
+
+/// Returns every process using a file, like `lsof` and `fuser` do.
+/// 
+/// A process is using a file when it has it open, memory mapped,
+/// or as its current working directory, root directory or executable.
+/// A process using the same file in multiple ways is returned once for each of them,
+/// except for memory mappings, which are returned once per file.
+/// 
+/// Processes that the current process is not allowed to inspect, or that exit while being inspected, are skipped,
+/// so without root privileges usually only the processes of the current user are checked.
+/// 
+/// Only the processes using this exact file are returned, see [`storage_holders`] to find the ones using any file of a storage.
+/// 
+/// On some filesystems (such as btrfs and overlayfs) `/proc/<pid>/maps` shows device numbers
+/// that differ from the storage identifier of the files,
+/// so the mappings with a matching inode number are confirmed by the identifier of the mapped path instead.
+/// Files mapped through a path that is not reachable from the current process
+/// (such as removed files, or files in another mount namespace) might then not be found.
+/// 
+/// # Errors
+/// 
+/// Returns [`io::Error`] when failing to read `/proc`.
+/// 
+/// # Examples
+/// 
+/// ```rust,no_run
+/// use fs_id::{process::holders, FileID};
+/// 
+/// fn main() -> std::io::Result<()> {
+///     for holder in holders(FileID::new("/some/file/path.txt")?)? {
+///         println!("process {} is still using the file", holder.pid());
+///     }
+///     Ok(())
+/// }
+/// ```
+pub fn holders(id: FileID) -> io::Result<Vec<Holder>> {
+	find_holders(|found| found == id, |mapped| mapped.internal_file_id() == id.internal_file_id())
+}
+
+/// Returns every process using any file of a storage, like `fuser -m` does.
+/// 
+/// This is the same as [`holders`], except that it matches every file with the given [`FileID::storage_id`],
+/// so it finds the processes that would prevent the storage from being unmounted.
+/// 
+/// Memory mappings are matched with the device numbers shown in `/proc/<pid>/maps`,
+/// which on some filesystems (such as btrfs) differ from the storage identifier,
+/// so on those filesystems the processes only mapping the files might not be found.
+/// 
+/// # Errors
+/// 
+/// Returns [`io::Error`] when failing to read `/proc`.
+/// 
+/// # Examples
+/// 
+/// ```rust,no_run
+/// use fs_id::{process::storage_holders, FileID};
+/// 
+/// fn main() -> std::io::Result<()> {
+///     for holder in storage_holders(FileID::new("/mnt/usb")?.storage_id())? {
+///         println!("process {} is still using the volume", holder.pid());
+///     }
+///     Ok(())
+/// }
+/// ```
+pub fn storage_holders(storage_id: u64) -> io::Result<Vec<Holder>> {
+	find_holders(|found| found.storage_id() == storage_id, |_| false)
+}
+
+/// Finds the processes using the files for which `matches` returns `true`.
+/// 
+/// The mappings whose identifier does not match but for which `confirm_mapping` returns `true`
+/// are checked again with the identifier of their path.
+fn find_holders<F: Fn(FileID) -> bool, C: Fn(FileID) -> bool>(
+	matches: F,
+	confirm_mapping: C
+) -> io::Result<Vec<Holder>> {
+	let mut holders = Vec::new();
+	for entry in fs::read_dir("/proc")? {
+		let Some(pid) = entry?.file_name().to_str().and_then(|name| name.parse().ok()) else {
+			continue;
+		};
+		// Errors only mean the process is inaccessible or gone, and what was found until then is still valid.
+		let _ = process_holders(pid, &matches, &confirm_mapping, &mut holders);
+	}
+	Ok(holders)
+}
+
+fn process_holders<F: Fn(FileID) -> bool, C: Fn(FileID) -> bool>(
+	pid: u32,
+	matches: F,
+	confirm_mapping: C,
+	holders: &mut Vec<Holder>
+) -> io::Result<()> {
+	let dir = Path::new("/proc").join(pid.to_string());
+	for (link, mode) in [("cwd", Mode::Cwd), ("root", Mode::Root), ("exe", Mode::Exe)] {
+		match FileID::new(dir.join(link).as_path()) {
+			Ok(id) if matches(id) => holders.push(Holder { pid, id, fd: None, mode }),
+			_ => {}
+		}
+	}
+	for fd in fds(pid)? {
+		let Ok((fd, id, _)) = fd else {
+			continue;
+		};
+		if !matches(id) {
+			continue;
+		}
+		let fdinfo = fs::read_to_string(dir.join("fdinfo").join(fd.to_string()));
+		if let Some(flags) = fdinfo.ok().as_deref().and_then(parse_flags) {
+			let mode = match flags & libc::O_ACCMODE {
+				libc::O_WRONLY => Mode::Write,
+				libc::O_RDWR => Mode::ReadWrite,
+				_ => Mode::Read,
+			};
+			holders.push(Holder { pid, id, fd: Some(fd), mode });
+		}
+	}
+	let maps = fs::read_to_string(dir.join("maps"))?;
+	// Files are usually mapped in multiple parts, but each file is only reported once.
+	let mut mapped = HashSet::new();
+	for line in maps.lines() {
+		let Some(mapped_id) = parse_mapped_id(line) else {
+			continue;
+		};
+		let id = if matches(mapped_id) {
+			mapped_id
+		} else if confirm_mapping(mapped_id) {
+			match parse_mapped_path(line).map(FileID::new) {
+				Some(Ok(id)) if id.internal_file_id() == mapped_id.internal_file_id() && matches(id) => id,
+				_ => continue,
+			}
+		} else {
+			continue;
+		};
+		if mapped.insert(id) {
+			holders.push(Holder { pid, id, fd: None, mode: Mode::Mapped });
+		}
+	}
+	Ok(())
+}
+
+/// Parses the (octal) flags the file descriptor was opened with from `/proc/<pid>/fdinfo/<fd>`.
+fn parse_flags(fdinfo: &str) -> Option<libc::c_int> {
+	let flags = fdinfo.lines().find_map(|line| line.strip_prefix("flags:"))?;
+	libc::c_int::from_str_radix(flags.trim(), 8).ok()
+}
+
+/// Parses the identifier of the mapped file from a line of `/proc/<pid>/maps`,
+/// which contains the device numbers (in hexadecimal) and the inode number.
+fn parse_mapped_id(line: &str) -> Option<FileID> {
+	let mut fields = line.split_ascii_whitespace().skip(3);
+	let (major, minor) = fields.next()?.split_once(':')?;
+	let inode = fields.next()?.parse().ok()?;
+	let major = u32::from_str_radix(major, 16).ok()?;
+	let minor = u32::from_str_radix(minor, 16).ok()?;
+	// Anonymous mappings have no file.
+	(inode != 0).then(|| FileID((libc::makedev(major, minor), inode)))
+}
+
+/// Parses the path of the mapped file from a line of `/proc/<pid>/maps`, if it has one.
+fn parse_mapped_path(line: &str) -> Option<&Path> {
+	// The other fields cannot contain slashes.
+	line.find('/').map(|start| Path::new(&line[start..]))
+}
+
+#[cfg(test)]
+mod tests {
+	use std::{fs::{self, File}, os::fd::AsRawFd, path::Path};
+	use crate::{FileID, GetID};
+	use super::{fds, holders, parse_mapped_id, parse_mapped_path, storage_holders, FdKind, Holder, Mode};
+
+	#[test]
+	fn check_own_fds() -> std::io::Result<()> {
+		let file = File::open("Cargo.toml")?;
+		let dir = File::open("src")?;
+		let pid = std::process::id();
+		let fds = fds(pid)?.collect::<std::io::Result<Vec<_>>>()?;
+		assert!(fds.contains(&(file.as_raw_fd(), file.get_id()?, FdKind::File)));
+		assert!(fds.contains(&(dir.as_raw_fd(), dir.get_id()?, FdKind::Directory)));
+		assert_eq!(FileID::of_process_fd(pid, file.as_raw_fd())?, FileID::new("Cargo.toml")?);
+		Ok(())
+	}
+
+	#[test]
+	fn check_holders() -> std::io::Result<()> {
+		let path = std::env::temp_dir().join(format!("fs-id-holders-{}", std::process::id()));
+		let file = File::create(&path)?;
+		let (id, fd) = (file.get_id()?, Some(file.as_raw_fd()));
+		let file_holders = holders(id);
+		let same_storage_holders = storage_holders(id.storage_id());
+		fs::remove_file(&path)?;
+		let pid = std::process::id();
+		let cwd = FileID::new(std::env::current_dir()?.as_path())?;
+		assert!(file_holders?.contains(&Holder { pid, id, fd, mode: Mode::Write }));
+		assert!(same_storage_holders?.contains(&Holder { pid, id, fd, mode: Mode::Write }));
+		assert!(holders(cwd)?.contains(&Holder { pid, id: cwd, fd: None, mode: Mode::Cwd }));
+		let exe = FileID::new(std::env::current_exe()?.as_path())?;
+		assert!(storage_holders(exe.storage_id())?.contains(&Holder { pid, id: exe, fd: None, mode: Mode::Exe }));
+		assert!(holders(exe)?.contains(&Holder { pid, id: exe, fd: None, mode: Mode::Mapped }));
+		Ok(())
+	}
+
+	#[test]
+	fn check_maps_parsing() {
+		let line = "55c7e9514000-55c7e9516000 r--p 00000000 fe:01 317783                     /usr/bin/head";
+		assert_eq!(parse_mapped_id(line), FileID::from_raw_parts(libc::makedev(0xfe, 1), 317783));
+		assert_eq!(parse_mapped_path(line), Some(Path::new("/usr/bin/head")));
+		assert_eq!(parse_mapped_id("7ffd0a5e6000-7ffd0a607000 rw-p 00000000 00:00 0                          [stack]"), None);
+	}
+}
@@ -0,0 +1,57 @@
+use std::{borrow::Cow, env::consts::OS};
+use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+use crate::FileID;
+
+/// The serialized representation of a [`FileID`].
+/// 
+/// The platform is stored alongside the identifiers,
+/// as identifiers obtained on different platforms cannot be meaningfully compared.
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "FileID")]
+struct Repr<'a> {
+	#[serde(borrow)]
+	platform: Cow<'a, str>,
+	storage_id: u64,
+	internal_file_id: u128,
+}
+
+impl Serialize for FileID {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		Repr {
+			platform: Cow::Borrowed(OS),
+			storage_id: self.storage_id(),
+			internal_file_id: self.internal_file_id(),
+		}.serialize(serializer)
+	}
+}
+
+impl<'de> Deserialize<'de> for FileID {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		let repr = Repr::deserialize(deserializer)?;
+		if repr.platform != OS {
+			return Err(D::Error::custom(format_args!(
+				"FileID was obtained on {}, but it is being read on {OS}",
+				repr.platform
+			)));
+		}
+		FileID::from_raw_parts(repr.storage_id, repr.internal_file_id)
+			.ok_or_else(|| D::Error::custom("internal file id is too large for this platform"))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::env::consts::OS;
+	use crate::FileID;
+
+	#[test]
+	fn check_representation() -> serde_json::Result<()> {
+		let file_id = FileID::from_raw_parts(0xfe01, 0x1234).unwrap();
+		let json = format!(r#"{{"platform":"{OS}","storage_id":65025,"internal_file_id":4660}}"#);
+		assert_eq!(serde_json::to_string(&file_id)?, json);
+		assert_eq!(serde_json::from_str::<FileID>(&json)?, file_id);
+		let foreign = json.replace(OS, if OS == "windows" { "linux" } else { "windows" });
+		assert!(serde_json::from_str::<FileID>(&foreign).is_err());
+		Ok(())
+	}
+}
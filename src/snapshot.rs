@@ -0,0 +1,253 @@
+//! Snapshots of directory trees, which can be compared to find out what changed between them,
+//! including which files were renamed or moved, by matching their [`FileID`]s.
+//! 
+//! # Examples
+//! 
+//! ```rust,no_run
+//! use fs_id::snapshot::{diff, Change, Snapshot};
+//! 
+//! fn main() -> std::io::Result<()> {
+//!     let old = Snapshot::new("/some/directory")?;
+//!     // ...
+//!     let new = Snapshot::new("/some/directory")?;
+//!     for change in diff(&old, &new) {
+//!         match change {
+//!             Change::Moved { from, to } => println!("{} -> {}", from.display(), to.display()),
+//!             change => println!("{change:?}"),
+//!         }
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+use std::{collections::{BTreeMap, HashMap}, io, path::{Path, PathBuf}, time::SystemTime};
+use crate::{collections::BuildFileIDHasher, walk::{Event, Walker}, FileID};
+
+/// The state of a path when a [`Snapshot`] was taken.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Record {
+	id: FileID,
+	size: u64,
+	modified: Option<SystemTime>,
+	is_dir: bool,
+}
+
+impl Record {
+	/// Returns the identifier of the file.
+	#[must_use]
+	pub const fn id(&self) -> FileID {
+		self.id
+	}
+
+	/// Returns the size of the file.
+	#[must_use]
+	pub const fn size(&self) -> u64 {
+		self.size
+	}
+
+	/// Returns the last modification time of the file, if it is available on the current platform.
+	#[must_use]
+	pub const fn modified(&self) -> Option<SystemTime> {
+		self.modified
+	}
+
+	/// Returns `true` if the file is a directory.
+	#[must_use]
+	pub const fn is_dir(&self) -> bool {
+		self.is_dir
+	}
+}
+
+/// The identifier, size and modification time of every path inside a directory tree.
+/// 
+/// Symbolic links are not followed, and are recorded as links.
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+	root: PathBuf,
+	records: BTreeMap<PathBuf, Record>,
+}
+
+impl Snapshot {
+	/// Takes a snapshot of the directory tree starting at `root`.
+	/// 
+	/// # Errors
+	/// 
+	/// Returns the first [`io::Error`] encountered while traversing the tree.
+	pub fn new<P: AsRef<Path>>(root: P) -> io::Result<Self> {
+		let root = root.as_ref().to_path_buf();
+		let mut records = BTreeMap::new();
+		for event in Walker::new(&root) {
+			let entry = match event? {
+				Event::File(entry) | Event::Alias { entry, .. } => entry,
+				_ => continue,
+			};
+			if entry.depth() == 0 {
+				continue;
+			}
+			let record = Record {
+				id: entry.id(),
+				size: entry.metadata().len(),
+				modified: entry.metadata().modified().ok(),
+				is_dir: entry.file_type().is_dir(),
+			};
+			let path = entry.path().strip_prefix(&root).unwrap_or(entry.path()).to_path_buf();
+			records.insert(path, record);
+		}
+		Ok(Self { root, records })
+	}
+
+	/// Returns the root of the snapshot.
+	#[must_use]
+	pub fn root(&self) -> &Path {
+		&self.root
+	}
+
+	/// Returns the record of a path, relative to the root of the snapshot.
+	#[must_use]
+	pub fn get<P: AsRef<Path> + ?Sized>(&self, path: &P) -> Option<&Record> {
+		self.records.get(path.as_ref())
+	}
+
+	/// Returns an iterator over the paths (relative to the root) and their records, sorted by path.
+	pub fn iter(&self) -> impl Iterator<Item = (&Path, &Record)> {
+		self.records.iter().map(|(path, record)| (path.as_path(), record))
+	}
+
+	/// Returns the number of paths in the snapshot, excluding the root.
+	#[must_use]
+	pub fn len(&self) -> usize {
+		self.records.len()
+	}
+
+	/// Returns `true` if the snapshot contains no paths other than the root.
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.records.is_empty()
+	}
+}
+
+/// A change between 2 [`Snapshot`]s, see [`diff`].
+/// 
+/// All the paths are relative to the roots of the snapshots.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Change {
+	/// A new file was created at the path.
+	Created(PathBuf),
+	/// The file at the path was deleted.
+	Deleted(PathBuf),
+	/// The size or modification time of the file at the path changed.
+	Modified(PathBuf),
+	/// The file was renamed or moved from one path to another.
+	Moved {
+		/// The old path of the file.
+		from: PathBuf,
+		/// The new path of the file.
+		to: PathBuf,
+	},
+}
+
+/// Compares 2 snapshots (usually of the same tree, taken at different times), and returns what changed.
+/// 
+/// Files are matched across paths by their [`FileID`], so a renamed file is reported as [`Change::Moved`]
+/// instead of being deleted and created again.
+/// When a directory is moved only the directory itself is reported, and not every path inside it.
+/// Files that were moved and modified are reported as both [`Change::Moved`] and [`Change::Modified`] (with the new path).
+/// 
+/// Directories are never reported as modified, since their modification time changes with their content.
+/// 
+/// Note that if a file is deleted and a new one reuses its identifier (see `linux::GenerationalFileID`),
+/// the new file will be reported as the old one being moved.
+#[must_use]
+pub fn diff(old: &Snapshot, new: &Snapshot) -> Vec<Change> {
+	let is_modified = |before: &Record, after: &Record| {
+		!after.is_dir && (before.size != after.size || before.modified != after.modified)
+	};
+	let mut changes = Vec::new();
+	// The paths that were created, grouped by identifier, to be matched with the deleted ones.
+	let mut created: HashMap<FileID, Vec<&Path>, BuildFileIDHasher> = HashMap::default();
+	for (path, after) in &new.records {
+		match old.records.get(path) {
+			Some(before) if before.id == after.id => {
+				if is_modified(before, after) {
+					changes.push(Change::Modified(path.clone()));
+				}
+			}
+			_ => created.entry(after.id).or_default().push(path),
+		}
+	}
+	let mut moves = BTreeMap::new();
+	let mut deleted = Vec::new();
+	for (path, before) in &old.records {
+		if new.records.get(path).map_or(false, |after| after.id == before.id) {
+			continue;
+		}
+		match created.get_mut(&before.id).filter(|paths| !paths.is_empty()) {
+			Some(paths) => {
+				let to = paths.remove(0);
+				if is_modified(before, &new.records[to]) {
+					changes.push(Change::Modified(to.to_path_buf()));
+				}
+				moves.insert(path.as_path(), to);
+			}
+			None => deleted.push(Change::Deleted(path.clone())),
+		}
+	}
+	changes.extend(deleted);
+	for (&from, &to) in &moves {
+		// Skip the paths that only moved because their parent directory did.
+		let implied = from.parent().zip(to.parent()).map_or(false, |(from_parent, to_parent)| {
+			from.file_name() == to.file_name() && moves.get(from_parent) == Some(&to_parent)
+		});
+		if !implied {
+			changes.push(Change::Moved { from: from.to_path_buf(), to: to.to_path_buf() });
+		}
+	}
+	let mut created: Vec<&Path> = created.into_values().flatten().collect();
+	created.sort();
+	changes.extend(created.into_iter().map(|path| Change::Created(path.to_path_buf())));
+	changes
+}
+
+#[cfg(test)]
+mod tests {
+	use std::{fs::{self, OpenOptions}, io::Write, path::{Path, PathBuf}};
+	use super::{diff, Change, Snapshot};
+
+	fn append(path: &Path) -> std::io::Result<()> {
+		OpenOptions::new().append(true).open(path)?.write_all(b"more")
+	}
+
+	#[test]
+	fn check_moves() -> std::io::Result<()> {
+		let dir = std::env::temp_dir().join(format!("fs-id-snapshot-{}", std::process::id()));
+		fs::create_dir_all(dir.join("sub"))?;
+		fs::write(dir.join("file"), "")?;
+		fs::write(dir.join("appended"), "")?;
+		fs::write(dir.join("sub/nested"), "")?;
+		fs::write(dir.join("deleted"), "")?;
+		let old = Snapshot::new(&dir);
+		fs::rename(dir.join("file"), dir.join("renamed"))?;
+		fs::rename(dir.join("sub"), dir.join("moved"))?;
+		append(&dir.join("renamed"))?;
+		append(&dir.join("appended"))?;
+		// Created before deleting the other file, so that its identifier cannot be reused.
+		fs::write(dir.join("created"), "")?;
+		// The content of the moved directory changes, but directories are never reported as modified.
+		fs::write(dir.join("moved/created"), "")?;
+		fs::remove_file(dir.join("deleted"))?;
+		let new = Snapshot::new(&dir);
+		fs::remove_dir_all(&dir)?;
+		let mut changes = diff(&old?, &new?);
+		changes.sort_by_key(|change| format!("{change:?}"));
+		assert_eq!(changes, [
+			Change::Created(PathBuf::from("created")),
+			Change::Created(PathBuf::from("moved/created")),
+			Change::Deleted(PathBuf::from("deleted")),
+			Change::Modified(PathBuf::from("appended")),
+			Change::Modified(PathBuf::from("renamed")),
+			Change::Moved { from: PathBuf::from("file"), to: PathBuf::from("renamed") },
+			Change::Moved { from: PathBuf::from("sub"), to: PathBuf::from("moved") },
+		]);
+		Ok(())
+	}
+}
@@ -0,0 +1,218 @@
+//! Detection of files being replaced, removed or truncated at their path, such as logs being rotated.
+//! 
+//! # Examples
+//! 
+//! ```rust,no_run
+//! use std::time::Duration;
+//! use fs_id::tracker::{Status, Tracker};
+//! 
+//! fn main() -> std::io::Result<()> {
+//!     let mut tracker = Tracker::new("/var/log/some.log")?;
+//!     loop {
+//!         match tracker.wait_for_change(Duration::from_secs(1))? {
+//!             Status::Replaced(_) => println!("the log was rotated, reopening it"),
+//!             Status::Truncated => println!("the log was truncated, reading it from the start"),
+//!             Status::Removed => println!("the log was removed, waiting for it to be created"),
+//!             Status::Unchanged => unreachable!(),
+//!         }
+//!     }
+//! }
+//! ```
+
+use std::{fs::{self, File}, io, path::{Path, PathBuf}, thread, time::Duration};
+use crate::{FileID, GetID};
+
+/// The state of a tracked path, compared to the last time it was checked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Status {
+	/// The path still points to the same file, and the file did not get shorter.
+	Unchanged,
+	/// The path now points to a different file, with the given identifier.
+	/// This happens when the file is moved away and a new one is created in its place
+	/// (like `logrotate` does by default), or when the path exists again after being [`Status::Removed`].
+	Replaced(FileID),
+	/// The path does not exist anymore.
+	/// This is only reported once, the following checks report [`Status::Unchanged`] until the path exists again.
+	Removed,
+	/// The path still points to the same file, but the file got shorter
+	/// (like `logrotate` does with `copytruncate`).
+	Truncated,
+}
+
+/// Remembers the identity and length of the file at a path, to detect when it changes.
+/// 
+/// The comparison is the same done by [`compare_ids`](crate::compare_ids),
+/// with the identifier of the file obtained when the tracker was created (or last changed).
+#[derive(Debug, Clone)]
+pub struct Tracker {
+	path: PathBuf,
+	id: FileID,
+	len: u64,
+	removed: bool,
+}
+
+impl Tracker {
+	/// Starts tracking the file the path currently points to.
+	/// 
+	/// # Errors
+	/// 
+	/// Returns [`io::Error`] when failing to obtain the identifier or the metadata of the path.
+	pub fn new<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+		let path = path.as_ref().to_path_buf();
+		let (id, len) = lookup(&path)?;
+		Ok(Self { path, id, len, removed: false })
+	}
+
+	/// Starts tracking an already open file, which is expected to be found at the given path.
+	/// 
+	/// If the path already points to a different file,
+	/// the first check will report it as [`Status::Replaced`] or [`Status::Removed`].
+	/// 
+	/// # Errors
+	/// 
+	/// Returns [`io::Error`] when failing to obtain the identifier or the metadata of the file.
+	pub fn with_file<P: AsRef<Path>>(path: P, file: &File) -> io::Result<Self> {
+		Ok(Self {
+			path: path.as_ref().to_path_buf(),
+			id: file.get_id()?,
+			len: file.metadata()?.len(),
+			removed: false,
+		})
+	}
+
+	/// Returns the tracked path.
+	#[must_use]
+	pub fn path(&self) -> &Path {
+		&self.path
+	}
+
+	/// Returns the identifier of the tracked file.
+	#[must_use]
+	pub const fn id(&self) -> FileID {
+		self.id
+	}
+
+	/// Checks the path again, and reports how it changed since the last check.
+	/// 
+	/// After a [`Status::Replaced`] the tracker starts tracking the new file,
+	/// so following checks will report [`Status::Unchanged`] until the new file changes.
+	/// A file that is truncated and then grows past its previous length between 2 checks
+	/// will not be reported as [`Status::Truncated`], so the checks should be frequent enough.
+	/// 
+	/// # Errors
+	/// 
+	/// Returns [`io::Error`] when failing to obtain the identifier or the metadata of the path,
+	/// unless it is because the path does not exist.
+	pub fn check(&mut self) -> io::Result<Status> {
+		let (id, len) = match lookup(&self.path) {
+			Ok(current) => current,
+			Err(error) if error.kind() == io::ErrorKind::NotFound => {
+				let was_removed = std::mem::replace(&mut self.removed, true);
+				return Ok(if was_removed { Status::Unchanged } else { Status::Removed });
+			}
+			Err(error) => return Err(error),
+		};
+		let previous_len = std::mem::replace(&mut self.len, len);
+		if std::mem::replace(&mut self.removed, false) || id != self.id {
+			self.id = id;
+			Ok(Status::Replaced(id))
+		} else if len < previous_len {
+			Ok(Status::Truncated)
+		} else {
+			Ok(Status::Unchanged)
+		}
+	}
+
+	/// Checks the path every `interval`, until the status is not [`Status::Unchanged`], and returns it.
+	/// 
+	/// # Errors
+	/// 
+	/// See [`Tracker::check`].
+	pub fn wait_for_change(&mut self, interval: Duration) -> io::Result<Status> {
+		loop {
+			match self.check()? {
+				Status::Unchanged => thread::sleep(interval),
+				status => return Ok(status),
+			}
+		}
+	}
+}
+
+/// Obtains the identifier and the length of the file at a path from the same lookup,
+/// so that they cannot belong to different files if the path is replaced concurrently.
+fn lookup(path: &Path) -> io::Result<(FileID, u64)> {
+	let metadata = fs::metadata(path)?;
+	match crate::sys::get_metadata_id(&metadata) {
+		Some(id) => Ok((id, metadata.len())),
+		None => {
+			let file = File::open(path)?;
+			Ok((file.get_id()?, file.metadata()?.len()))
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::{fs, io::Write, sync::atomic::{AtomicBool, Ordering}, thread};
+	use super::{Status, Tracker};
+
+	#[test]
+	fn check_rotations() -> std::io::Result<()> {
+		let dir = std::env::temp_dir().join(format!("fs-id-tracker-{}", std::process::id()));
+		fs::create_dir_all(&dir)?;
+		let log = dir.join("log");
+		fs::write(&log, "some lines")?;
+		let mut tracker = Tracker::new(&log)?;
+		let mut statuses = vec![tracker.check()?];
+		fs::write(&log, "")?;
+		statuses.push(tracker.check()?);
+		fs::rename(&log, dir.join("log.1"))?;
+		statuses.push(tracker.check()?);
+		statuses.push(tracker.check()?);
+		fs::write(&log, "")?;
+		statuses.push(tracker.check()?);
+		statuses.push(tracker.check()?);
+		fs::remove_dir_all(&dir)?;
+		assert_eq!(statuses[..4], [Status::Unchanged, Status::Truncated, Status::Removed, Status::Unchanged]);
+		assert!(matches!(statuses[4], Status::Replaced(id) if id == tracker.id()));
+		assert_eq!(statuses[5], Status::Unchanged);
+		Ok(())
+	}
+
+	#[test]
+	fn check_concurrent_rotations() -> std::io::Result<()> {
+		let dir = std::env::temp_dir().join(format!("fs-id-tracker-concurrent-{}", std::process::id()));
+		fs::create_dir_all(&dir)?;
+		let log = dir.join("log");
+		fs::write(&log, "some lines")?;
+		let mut tracker = Tracker::new(&log)?;
+		let done = AtomicBool::new(false);
+		// The rotated logs only grow, so the tracker must never see a truncation,
+		// even when a rotation happens while it is checking the path.
+		let (rotations, statuses) = thread::scope(|scope| {
+			let rotator = scope.spawn(|| {
+				// The rotated logs are kept, so that their identifiers cannot be reused by the new ones.
+				let result = (0..1000).try_for_each(|i| {
+					fs::rename(&log, dir.join(format!("log.{i}")))?;
+					// The new log is empty for a while, then grows back.
+					let mut file = fs::File::create(&log)?;
+					thread::yield_now();
+					file.write_all(b"some lines")
+				});
+				done.store(true, Ordering::Relaxed);
+				result
+			});
+			let mut statuses = Vec::new();
+			while !done.load(Ordering::Relaxed) {
+				statuses.push(tracker.check());
+			}
+			(rotator.join().unwrap(), statuses)
+		});
+		fs::remove_dir_all(&dir)?;
+		rotations?;
+		for status in statuses {
+			assert_ne!(status?, Status::Truncated);
+		}
+		Ok(())
+	}
+}
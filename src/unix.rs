@@ -1,18 +1,48 @@
-use std::{io, mem, os::fd::AsRawFd};
+use std::{ffi::CString, fs::Metadata, io, mem, os::{fd::{AsRawFd, RawFd}, unix::{ffi::OsStrExt, fs::MetadataExt}}, path::Path};
 use crate::{GetID, FileID};
 
 pub type FileIDImpl = (u64, u64);
 
 impl<T: AsRawFd> GetID for T {
 	fn get_id(&self) -> io::Result<FileID> {
-		let fd = self.as_raw_fd();
-		unsafe {
-			let mut buf = mem::zeroed();
-			if libc::fstat64(fd, &mut buf) == 0 {
-				Ok(FileID((buf.st_dev, buf.st_ino)))
-			} else {
-				Err(io::Error::last_os_error())				
-			}
+		get_fd_id(self.as_raw_fd())
+	}
+}
+
+pub fn get_fd_id(fd: RawFd) -> io::Result<FileID> {
+	unsafe {
+		let mut buf = mem::zeroed();
+		if libc::fstat64(fd, &mut buf) == 0 {
+			Ok(FileID((buf.st_dev, buf.st_ino)))
+		} else {
+			Err(io::Error::last_os_error())				
+		}
+	}
+}
+
+pub fn get_metadata_id(metadata: &Metadata) -> Option<FileID> {
+	Some(FileID((metadata.dev(), metadata.ino())))
+}
+
+pub fn get_path_id(path: &Path) -> io::Result<FileID> {
+	stat_path(path, libc::stat64)
+}
+
+pub fn get_id_nofollow(path: &Path) -> io::Result<FileID> {
+	stat_path(path, libc::lstat64)
+}
+
+fn stat_path(
+	path: &Path,
+	stat: unsafe extern "C" fn(*const libc::c_char, *mut libc::stat64) -> libc::c_int
+) -> io::Result<FileID> {
+	let path = CString::new(path.as_os_str().as_bytes())?;
+	unsafe {
+		let mut buf = mem::zeroed();
+		if stat(path.as_ptr(), &mut buf) == 0 {
+			Ok(FileID((buf.st_dev, buf.st_ino)))
+		} else {
+			Err(io::Error::last_os_error())
 		}
 	}
 }
@@ -0,0 +1,173 @@
+//! Change detection for files, by combining their [`FileID`] with their metadata.
+//! 
+//! # Examples
+//! 
+//! ```rust,no_run
+//! use fs_id::version::FileVersion;
+//! 
+//! fn main() -> std::io::Result<()> {
+//!     let hashed = FileVersion::from_path("/some/file/path.txt")?;
+//!     // ...
+//!     if FileVersion::from_path("/some/file/path.txt")?.is_unchanged_since(&hashed) {
+//!         println!("no need to hash the file again");
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+use std::{fs::{self, File, Metadata}, io, path::Path, time::SystemTime};
+use crate::{FileID, GetID};
+
+/// A [`FileID`] paired with the metadata that changes when the file is modified.
+/// 
+/// This includes the size and modification time of the file,
+/// and on Unix the time the file's metadata last changed (`ctime`),
+/// which unlike the modification time cannot be set to an arbitrary value.
+/// On Linux the generation number of the inode can also be included, see [`FileVersion::new_with_generation`].
+/// 
+/// All the times have the precision provided by the platform, usually nanoseconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FileVersion {
+	id: FileID,
+	size: u64,
+	modified: Option<SystemTime>,
+	changed: Option<SystemTime>,
+	generation: Option<u32>,
+}
+
+impl FileVersion {
+	/// Obtains the version of an open file.
+	/// 
+	/// # Errors
+	/// 
+	/// Returns [`io::Error`] when failing to obtain the identifier or the metadata of the file.
+	pub fn new(file: &File) -> io::Result<Self> {
+		Ok(Self::from_metadata(file.get_id()?, &file.metadata()?))
+	}
+
+	/// Obtains the version of a path.
+	/// 
+	/// Like [`FileID::new`], only search permission on the path's parent directories is required.
+	/// 
+	/// # Errors
+	/// 
+	/// Returns [`io::Error`] when failing to obtain the identifier or the metadata of the path.
+	pub fn from_path<P: AsRef<Path> + ?Sized>(path: &P) -> io::Result<Self> {
+		let path = path.as_ref();
+		Ok(Self::from_metadata(FileID::new(path)?, &fs::metadata(path)?))
+	}
+
+	/// Obtains the version of an open file, including the generation number of its inode,
+	/// see [`GenerationalFileID`](crate::linux::GenerationalFileID).
+	/// 
+	/// # Errors
+	/// 
+	/// Returns [`io::Error`] when failing to obtain the identifier, the metadata or the generation number of the file.
+	#[cfg(target_os = "linux")]
+	pub fn new_with_generation(file: &File) -> io::Result<Self> {
+		let generational = crate::linux::GenerationalFileID::new(file)?;
+		Ok(Self {
+			generation: Some(generational.generation()),
+			..Self::from_metadata(generational.file_id(), &file.metadata()?)
+		})
+	}
+
+	fn from_metadata(id: FileID, metadata: &Metadata) -> Self {
+		Self {
+			id,
+			size: metadata.len(),
+			modified: metadata.modified().ok(),
+			changed: changed(metadata),
+			generation: None,
+		}
+	}
+
+	/// Returns the identifier of the file.
+	#[must_use]
+	pub const fn id(&self) -> FileID {
+		self.id
+	}
+
+	/// Returns the size of the file.
+	#[must_use]
+	pub const fn size(&self) -> u64 {
+		self.size
+	}
+
+	/// Returns the last modification time of the file, if it is available on the current platform.
+	#[must_use]
+	pub const fn modified(&self) -> Option<SystemTime> {
+		self.modified
+	}
+
+	/// Returns the last time the metadata of the file changed (`ctime`), which is only available on Unix.
+	#[must_use]
+	pub const fn changed(&self) -> Option<SystemTime> {
+		self.changed
+	}
+
+	/// Returns the generation number of the file's inode, if it was obtained.
+	#[must_use]
+	pub const fn generation(&self) -> Option<u32> {
+		self.generation
+	}
+
+	/// Returns `true` if this version is of the same file as `earlier`, and the file did not change since then.
+	/// 
+	/// The generation numbers are only compared if both versions have one.
+	#[must_use]
+	pub fn is_unchanged_since(&self, earlier: &Self) -> bool {
+		let same_generation = match (self.generation, earlier.generation) {
+			(Some(generation), Some(earlier_generation)) => generation == earlier_generation,
+			_ => true,
+		};
+		self.id == earlier.id
+			&& self.size == earlier.size
+			&& self.modified == earlier.modified
+			&& self.changed == earlier.changed
+			&& same_generation
+	}
+}
+
+impl GetID for FileVersion {
+	/// Returns the identifier of the file, see [`FileVersion::id`].
+	fn get_id(&self) -> io::Result<FileID> {
+		Ok(self.id)
+	}
+}
+
+#[cfg(unix)]
+fn changed(metadata: &Metadata) -> Option<SystemTime> {
+	use std::{os::unix::fs::MetadataExt, time::{Duration, UNIX_EPOCH}};
+	let nanos = Duration::from_nanos(u64::try_from(metadata.ctime_nsec()).ok()?);
+	match u64::try_from(metadata.ctime()) {
+		Ok(secs) => UNIX_EPOCH.checked_add(Duration::from_secs(secs) + nanos),
+		Err(_) => UNIX_EPOCH.checked_sub(Duration::from_secs(metadata.ctime().unsigned_abs()))?.checked_add(nanos),
+	}
+}
+
+#[cfg(not(unix))]
+fn changed(_metadata: &Metadata) -> Option<SystemTime> {
+	None
+}
+
+#[cfg(test)]
+mod tests {
+	use std::fs;
+	use super::FileVersion;
+
+	#[test]
+	fn check_changes() -> std::io::Result<()> {
+		let path = std::env::temp_dir().join(format!("fs-id-version-{}", std::process::id()));
+		fs::write(&path, "original")?;
+		let original = FileVersion::from_path(&path);
+		let same = FileVersion::new(&fs::File::open(&path)?);
+		fs::write(&path, "modified")?;
+		let modified = FileVersion::from_path(&path);
+		fs::remove_file(&path)?;
+		let original = original?;
+		assert!(same?.is_unchanged_since(&original));
+		assert!(!modified?.is_unchanged_since(&original));
+		Ok(())
+	}
+}
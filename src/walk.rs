@@ -0,0 +1,548 @@
+//! Recursive directory traversal that reports every file exactly once, no matter how many hard links it has.
+//! 
+//! # Examples
+//! 
+//! ```rust,no_run
+//! use fs_id::walk::{Event, Walker};
+//! 
+//! fn main() -> std::io::Result<()> {
+//!     for event in Walker::new("/some/directory") {
+//!         match event? {
+//!             Event::File(entry) => println!("{} {}", entry.id(), entry.path().display()),
+//!             Event::Alias { entry, original } => {
+//!                 println!("{} is the same file as {}", entry.path().display(), original.display());
+//!             }
+//!             Event::Cycle { path, ancestor } => {
+//!                 println!("{} loops back to {}", path.display(), ancestor.display());
+//!             }
+//!             _ => {}
+//!         }
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+use std::{collections::{hash_map, HashMap, HashSet}, ffi::OsString, fs::{self, FileType, Metadata, ReadDir}, io, path::{Path, PathBuf}};
+use crate::{collections::{BuildFileIDHasher, FileIDMap}, FileID};
+
+/// A builder to configure a recursive directory traversal.
+#[derive(Debug, Clone)]
+pub struct Walker {
+	root: PathBuf,
+	follow_links: bool,
+	same_storage: bool,
+}
+
+impl Walker {
+	/// Creates a traversal starting at `root`, which is included in the results.
+	/// 
+	/// By default symbolic links are not followed.
+	pub fn new<P: AsRef<Path>>(root: P) -> Self {
+		Self {
+			root: root.as_ref().to_path_buf(),
+			follow_links: false,
+			same_storage: false,
+		}
+	}
+
+	/// Sets whether symbolic links should be followed.
+	/// 
+	/// When following links, entries are identified by the file the link points to,
+	/// and a link to a file (or directory) that was already reported will be reported as an alias of it.  
+	/// A link to one of the directories currently being traversed (such as `a/link -> ..`)
+	/// is reported as an [`Event::Cycle`] instead, and is never descended into.
+	#[must_use]
+	pub fn follow_links(mut self, follow_links: bool) -> Self {
+		self.follow_links = follow_links;
+		self
+	}
+
+	/// Sets whether the traversal should stay on the storage containing the root,
+	/// like `find -xdev` or `du -x`.
+	/// 
+	/// When enabled, entries with a different [`FileID::storage_id`] than the root
+	/// (such as other filesystems mounted inside the tree) are skipped entirely, and not descended into.
+	#[must_use]
+	pub fn same_storage(mut self, same_storage: bool) -> Self {
+		self.same_storage = same_storage;
+		self
+	}
+
+	/// Traverses the whole tree and groups the entries by their identifier.
+	/// 
+	/// The value of each file is the [`Entry`] of the first path the file was found at,
+	/// and the paths of the map contain all the paths the file was found at (including the first one),
+	/// except for the ones that were reported as an [`Event::Cycle`].
+	/// 
+	/// # Errors
+	/// 
+	/// Returns the first [`io::Error`] encountered during the traversal,
+	/// see [`Walker::group_by_id_lossy`] to skip the entries that cannot be read instead.
+	/// 
+	/// # Examples
+	/// 
+	/// ```rust,no_run
+	/// use fs_id::walk::Walker;
+	/// 
+	/// fn main() -> std::io::Result<()> {
+	///     let files = Walker::new("/some/directory").group_by_id()?;
+	///     for (id, entry, paths) in files.iter_with_paths() {
+	///         if paths.len() > 1 {
+	///             println!("{id} ({} bytes) is linked at {paths:?}", entry.metadata().len());
+	///         }
+	///     }
+	///     Ok(())
+	/// }
+	/// ```
+	pub fn group_by_id(self) -> io::Result<FileIDMap<Entry>> {
+		let mut files = FileIDMap::new();
+		for event in self {
+			group(&mut files, event?);
+		}
+		Ok(files)
+	}
+
+	/// Traverses the whole tree and groups the entries by their identifier like [`Walker::group_by_id`],
+	/// skipping the entries that cannot be read (for example because of missing permissions).
+	/// 
+	/// Every error encountered is passed to `on_error`, and the returned map only includes the files that could be read.
+	pub fn group_by_id_lossy<F: FnMut(io::Error)>(self, mut on_error: F) -> FileIDMap<Entry> {
+		let mut files = FileIDMap::new();
+		for event in self {
+			match event {
+				Ok(event) => group(&mut files, event),
+				Err(error) => on_error(error),
+			}
+		}
+		files
+	}
+}
+
+impl IntoIterator for Walker {
+	type Item = io::Result<Event>;
+	type IntoIter = Walk;
+
+	fn into_iter(self) -> Self::IntoIter {
+		Walk {
+			root: Some(self.root),
+			follow_links: self.follow_links,
+			same_storage: self.same_storage,
+			root_storage_id: 0,
+			stack: Vec::new(),
+			seen: HashMap::default(),
+			pending_error: None,
+		}
+	}
+}
+
+/// A path found during the traversal, along with its identifier and metadata.
+#[derive(Debug, Clone)]
+pub struct Entry {
+	path: PathBuf,
+	id: FileID,
+	metadata: Metadata,
+	depth: usize,
+}
+
+impl Entry {
+	/// Returns the path of the entry, which starts with the root of the traversal.
+	#[must_use]
+	pub fn path(&self) -> &Path {
+		&self.path
+	}
+
+	/// Consumes the entry, returning its path.
+	#[must_use]
+	pub fn into_path(self) -> PathBuf {
+		self.path
+	}
+
+	/// Returns the identifier of the entry.
+	#[must_use]
+	pub const fn id(&self) -> FileID {
+		self.id
+	}
+
+	/// Returns the metadata of the entry.
+	/// 
+	/// If symbolic links are not being followed, this is the metadata of the link itself.
+	#[must_use]
+	pub const fn metadata(&self) -> &Metadata {
+		&self.metadata
+	}
+
+	/// Returns the file type of the entry.
+	#[must_use]
+	pub fn file_type(&self) -> FileType {
+		self.metadata.file_type()
+	}
+
+	/// Returns how many directories deep the entry is, the root has a depth of 0.
+	#[must_use]
+	pub const fn depth(&self) -> usize {
+		self.depth
+	}
+}
+
+/// An event produced while traversing a directory tree.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub enum Event {
+	/// A file (or directory, etc.) found for the first time.
+	File(Entry),
+	/// A path to a file that was already reported, such as a hard link.
+	/// Directories reported as aliases are not descended into again.
+	Alias {
+		/// The entry of the new path.
+		entry: Entry,
+		/// The path the file was first reported at.
+		original: PathBuf,
+	},
+	/// A directory that is also one of its own ancestors in the traversal,
+	/// usually reached by following a symbolic link.
+	/// The directory is not descended into again, which would otherwise loop forever.
+	Cycle {
+		/// The path of the directory.
+		path: PathBuf,
+		/// The path of the ancestor directory with the same identifier.
+		ancestor: PathBuf,
+	},
+}
+
+fn group(files: &mut FileIDMap<Entry>, event: Event) {
+	match event {
+		Event::File(entry) => {
+			let (id, path) = (entry.id, entry.path.clone());
+			files.insert(id, entry);
+			files.push_path(&id, path);
+		}
+		Event::Alias { entry, .. } => files.push_path(&entry.id, entry.path),
+		Event::Cycle { .. } => {}
+	}
+}
+
+#[derive(Debug)]
+struct Ancestor {
+	entries: ReadDir,
+	depth: usize,
+	id: FileID,
+	path: PathBuf,
+}
+
+/// An iterator over the events of a directory traversal, created by [`Walker`].
+/// 
+/// The traversal continues after an error, skipping the entry (or the content of the directory) that could not be read.
+/// The errors contain the path that could not be read in their message.
+#[derive(Debug)]
+pub struct Walk {
+	root: Option<PathBuf>,
+	follow_links: bool,
+	same_storage: bool,
+	root_storage_id: u64,
+	stack: Vec<Ancestor>,
+	seen: HashMap<FileID, PathBuf, BuildFileIDHasher>,
+	pending_error: Option<io::Error>,
+}
+
+impl Walk {
+	fn lookup(&self, path: &Path) -> io::Result<(Metadata, FileID)> {
+		let metadata = if self.follow_links { fs::metadata(path)? } else { fs::symlink_metadata(path)? };
+		// Where the metadata contains the identifier, the path is only queried once,
+		// so the identifier cannot belong to a different file if the path is replaced in between.
+		let id = match crate::sys::get_metadata_id(&metadata) {
+			Some(id) => id,
+			None if self.follow_links => FileID::new(path)?,
+			None => FileID::new_nofollow(path)?,
+		};
+		Ok((metadata, id))
+	}
+
+	fn visit(&mut self, path: PathBuf, depth: usize) -> io::Result<Option<Event>> {
+		let (metadata, id) = self.lookup(&path).map_err(|error| with_path(error, &path))?;
+		if depth == 0 {
+			self.root_storage_id = id.storage_id();
+		} else if self.same_storage && id.storage_id() != self.root_storage_id {
+			return Ok(None);
+		}
+		let entry = Entry { path, id, metadata, depth };
+		match self.seen.entry(id) {
+			hash_map::Entry::Occupied(original) => {
+				if entry.metadata.is_dir() {
+					if let Some(ancestor) = self.stack.iter().find(|ancestor| ancestor.id == id) {
+						return Ok(Some(Event::Cycle {
+							path: entry.path,
+							ancestor: ancestor.path.clone(),
+						}));
+					}
+				}
+				Ok(Some(Event::Alias {
+					entry,
+					original: original.get().clone(),
+				}))
+			}
+			hash_map::Entry::Vacant(vacant) => {
+				vacant.insert(entry.path.clone());
+				if entry.metadata.is_dir() {
+					match fs::read_dir(&entry.path) {
+						Ok(entries) => self.stack.push(Ancestor {
+							entries,
+							depth,
+							id,
+							path: entry.path.clone(),
+						}),
+						Err(error) => self.pending_error = Some(with_path(error, &entry.path)),
+					}
+				}
+				Ok(Some(Event::File(entry)))
+			}
+		}
+	}
+}
+
+impl Iterator for Walk {
+	type Item = io::Result<Event>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		if let Some(error) = self.pending_error.take() {
+			return Some(Err(error));
+		}
+		if let Some(root) = self.root.take() {
+			return self.visit(root, 0).transpose();
+		}
+		loop {
+			let dir = self.stack.last_mut()?;
+			let depth = dir.depth + 1;
+			match dir.entries.next() {
+				Some(Ok(entry)) => {
+					if let Some(result) = self.visit(entry.path(), depth).transpose() {
+						return Some(result);
+					}
+				}
+				Some(Err(error)) => return Some(Err(with_path(error, &dir.path))),
+				None => {
+					self.stack.pop();
+				}
+			}
+		}
+	}
+}
+
+/// Adds the path that could not be read to an error, keeping its kind.
+fn with_path(error: io::Error, path: &Path) -> io::Error {
+	io::Error::new(error.kind(), format!("{}: {error}", path.display()))
+}
+
+/// Finds every path with the given identifier inside the given directories, the inverse of [`FileID::new`].
+/// 
+/// Only the parts of the directories on the same storage as the identifier are traversed,
+/// including storages mounted inside them (see [`Walker::same_storage`]).  
+/// Symbolic links are not followed, so only hard links to the file are found.
+/// Each link is returned once, even if it can be reached from multiple directories or mounts.
+/// 
+/// Entries that cannot be read (for example because of missing permissions) are skipped.
+/// 
+/// # Platform-specific behavior
+/// 
+/// On Linux the mounts of the storage inside the directories are found in `/proc/self/mountinfo`,
+/// while on other platforms the directories on other storages are traversed until reaching them.
+/// 
+/// On Unix the search stops as soon as all the hard links of the file (`st_nlink`) have been found.
+/// 
+/// # Errors
+/// 
+/// Returns [`io::Error`] when failing to obtain the identifier of any of the directories,
+/// or on Linux when failing to read `/proc/self/mountinfo`.
+/// 
+/// # Examples
+/// 
+/// ```rust,no_run
+/// use fs_id::{walk::find_paths, FileID};
+/// 
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let id: FileID = "fe00:1060cb".parse()?;
+///     for path in find_paths(id, ["/home", "/mnt/backup"])? {
+///         println!("{}", path.display());
+///     }
+///     Ok(())
+/// }
+/// ```
+pub fn find_paths<I: IntoIterator<Item = P>, P: AsRef<Path>>(id: FileID, roots: I) -> io::Result<Vec<PathBuf>> {
+	let mut paths = Vec::new();
+	// The links already found, as their parent directory and name, since overlapping roots or mounts
+	// would otherwise report the same link at multiple paths, and stop the search before finding the others.
+	let mut found = HashSet::new();
+	let mut links = None;
+	for root in roots {
+		for dir in storage_dirs(root.as_ref(), id.storage_id())? {
+			for event in Walker::new(dir).same_storage(true) {
+				let entry = match event {
+					Ok(Event::File(entry) | Event::Alias { entry, .. }) if entry.id == id => entry,
+					_ => continue,
+				};
+				if !found.insert(link_of(&entry.path)) {
+					continue;
+				}
+				if links.is_none() {
+					links = link_count(&entry.metadata);
+				}
+				paths.push(entry.path);
+				if links.map_or(false, |links| paths.len() as u64 >= links) {
+					return Ok(paths);
+				}
+			}
+		}
+	}
+	Ok(paths)
+}
+
+fn link_of(path: &Path) -> (Option<FileID>, Option<OsString>) {
+	let parent = match path.parent() {
+		Some(parent) if parent.as_os_str().is_empty() => Some(Path::new(".")),
+		parent => parent,
+	};
+	(parent.and_then(|parent| FileID::new(parent).ok()), path.file_name().map(ToOwned::to_owned))
+}
+
+/// Returns the directories inside `root` where a storage is mounted, including `root` itself if it is on the storage.
+#[cfg(target_os = "linux")]
+fn storage_dirs(root: &Path, storage_id: u64) -> io::Result<Vec<PathBuf>> {
+	let mut dirs = Vec::new();
+	if FileID::new(root)?.storage_id() == storage_id {
+		dirs.push(root.to_path_buf());
+	}
+	let canonical_root = fs::canonicalize(root)?;
+	for info in crate::mounts::mounts()? {
+		let relative = match info.mount_point().strip_prefix(&canonical_root) {
+			Ok(relative) if !relative.as_os_str().is_empty() => relative,
+			_ => continue,
+		};
+		let dir = root.join(relative);
+		// The device numbers in `/proc/self/mountinfo` do not always match the storage identifiers (such as on btrfs),
+		// and the mount might be hidden by another one mounted over it, so the mount point itself is checked.
+		if FileID::new(dir.as_path()).map_or(false, |id| id.storage_id() == storage_id) {
+			dirs.push(dir);
+		}
+	}
+	Ok(dirs)
+}
+
+/// Returns the directories inside `root` where a storage is mounted, including `root` itself if it is on the storage.
+#[cfg(not(target_os = "linux"))]
+fn storage_dirs(root: &Path, storage_id: u64) -> io::Result<Vec<PathBuf>> {
+	let mut dirs = Vec::new();
+	if FileID::new(root)?.storage_id() == storage_id {
+		dirs.push(root.to_path_buf());
+	} else {
+		find_storage_dirs(root, storage_id, &mut dirs);
+	}
+	Ok(dirs)
+}
+
+/// Descends through the directories on other storages, until reaching the ones on the given storage.
+#[cfg(not(target_os = "linux"))]
+fn find_storage_dirs(dir: &Path, storage_id: u64, dirs: &mut Vec<PathBuf>) {
+	let Ok(entries) = fs::read_dir(dir) else {
+		return;
+	};
+	for entry in entries.flatten() {
+		if !entry.file_type().map_or(false, |file_type| file_type.is_dir()) {
+			continue;
+		}
+		let path = entry.path();
+		match FileID::new_nofollow(&path) {
+			Ok(id) if id.storage_id() == storage_id => dirs.push(path),
+			Ok(_) => find_storage_dirs(&path, storage_id, dirs),
+			Err(_) => {}
+		}
+	}
+}
+
+#[cfg(unix)]
+fn link_count(metadata: &Metadata) -> Option<u64> {
+	use std::os::unix::fs::MetadataExt;
+	// The link count of directories also includes their subdirectories.
+	Some(if metadata.is_dir() { 1 } else { metadata.nlink() })
+}
+
+#[cfg(not(unix))]
+fn link_count(_metadata: &Metadata) -> Option<u64> {
+	None
+}
+
+#[cfg(test)]
+mod tests {
+	use std::fs;
+	use crate::FileID;
+	use super::{find_paths, Event, Walker};
+
+	#[test]
+	fn check_hard_links() -> std::io::Result<()> {
+		let dir = std::env::temp_dir().join(format!("fs-id-walk-{}", std::process::id()));
+		fs::create_dir_all(dir.join("sub"))?;
+		fs::write(dir.join("file"), "")?;
+		fs::write(dir.join("other"), "")?;
+		fs::hard_link(dir.join("file"), dir.join("sub/link"))?;
+		let events = Walker::new(&dir).into_iter().collect::<std::io::Result<Vec<_>>>();
+		let groups = Walker::new(&dir).group_by_id();
+		fs::remove_dir_all(&dir)?;
+		let events = events?;
+		assert_eq!(events.iter().filter(|event| matches!(event, Event::File(_))).count(), 4);
+		assert_eq!(events.iter().filter(|event| matches!(event, Event::Alias { .. })).count(), 1);
+		let groups = groups?;
+		assert_eq!(groups.len(), 4);
+		assert_eq!(groups.iter_with_paths().filter(|(_, _, paths)| paths.len() == 2).count(), 1);
+		Ok(())
+	}
+
+	#[test]
+	fn check_error_paths() {
+		let error = Walker::new("missing").into_iter().next().unwrap().unwrap_err();
+		assert_eq!(error.kind(), std::io::ErrorKind::NotFound);
+		assert!(error.to_string().starts_with("missing: "));
+	}
+
+	#[test]
+	fn check_overlapping_roots() -> std::io::Result<()> {
+		let dir = std::env::temp_dir().join(format!("fs-id-overlapping-{}", std::process::id()));
+		fs::create_dir_all(dir.join("sub"))?;
+		fs::write(dir.join("file"), "")?;
+		fs::hard_link(dir.join("file"), dir.join("sub/link"))?;
+		let paths = FileID::new(dir.join("file").as_path()).and_then(|id| find_paths(id, [dir.join("sub"), dir.clone()]));
+		fs::remove_dir_all(&dir)?;
+		let mut paths = paths?;
+		paths.sort();
+		assert_eq!(paths, [dir.join("file"), dir.join("sub/link")]);
+		Ok(())
+	}
+
+	#[test]
+	#[cfg(unix)]
+	fn check_cycles() -> std::io::Result<()> {
+		let dir = std::env::temp_dir().join(format!("fs-id-cycles-{}", std::process::id()));
+		fs::create_dir_all(dir.join("a"))?;
+		std::os::unix::fs::symlink("..", dir.join("a/link"))?;
+		let events = Walker::new(&dir).follow_links(true).into_iter().collect::<std::io::Result<Vec<_>>>();
+		fs::remove_dir_all(&dir)?;
+		let events = events?;
+		assert_eq!(events.len(), 3);
+		assert!(matches!(
+			&events[2],
+			Event::Cycle { path, ancestor } if *path == dir.join("a/link") && *ancestor == dir
+		));
+		Ok(())
+	}
+
+	#[test]
+	fn check_find_paths() -> std::io::Result<()> {
+		let dir = std::env::temp_dir().join(format!("fs-id-find-{}", std::process::id()));
+		fs::create_dir_all(dir.join("sub"))?;
+		fs::write(dir.join("file"), "")?;
+		fs::hard_link(dir.join("file"), dir.join("sub/link"))?;
+		let paths = crate::FileID::new(dir.join("file").as_path()).and_then(|id| super::find_paths(id, [&dir]));
+		fs::remove_dir_all(&dir)?;
+		let mut paths = paths?;
+		paths.sort();
+		assert_eq!(paths, [dir.join("file"), dir.join("sub/link")]);
+		Ok(())
+	}
+}
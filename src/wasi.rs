@@ -1,4 +1,4 @@
-use std::{io, mem, os::fd::AsRawFd};
+use std::{fs::{File, Metadata}, io, mem, os::fd::AsRawFd, path::Path};
 use wasi::{wasi_snapshot_preview1::fd_filestat_get, Filestat};
 use crate::{GetID, FileID};
 
@@ -17,3 +17,35 @@ impl<T: AsRawFd> GetID for T {
 		}
 	}
 }
+
+pub fn get_metadata_id(_metadata: &Metadata) -> Option<FileID> {
+	// `std::os::wasi::fs::MetadataExt` is not stable yet.
+	None
+}
+
+pub fn get_path_id(path: &Path) -> io::Result<FileID> {
+	path_filestat(path, wasi::LOOKUPFLAGS_SYMLINK_FOLLOW)
+}
+
+pub fn get_id_nofollow(path: &Path) -> io::Result<FileID> {
+	path_filestat(path, 0)
+}
+
+fn path_filestat(path: &Path, flags: wasi::Lookupflags) -> io::Result<FileID> {
+	let Some(name) = path.file_name() else {
+		// "/" and ".." cannot be symlinks, so following them is fine.
+		return File::open(path)?.get_id();
+	};
+	let name = name.to_str().ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "path is not valid UTF-8"))?;
+	let parent = match path.parent() {
+		Some(parent) if !parent.as_os_str().is_empty() => parent,
+		_ => Path::new("."),
+	};
+	let dir = File::open(parent)?;
+	unsafe {
+		match wasi::path_filestat_get(dir.as_raw_fd() as u32, flags, name) {
+			Ok(filestat) => Ok(FileID((filestat.dev, filestat.ino))),
+			Err(errno) => Err(io::Error::from_raw_os_error(errno.raw() as i32))
+		}
+	}
+}
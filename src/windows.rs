@@ -1,5 +1,9 @@
-use std::{io, mem, os::windows::io::AsRawHandle, ffi::c_void};
-use winapi::um::{winbase::GetFileInformationByHandleEx, minwinbase::FileIdInfo, fileapi::FILE_ID_INFO};
+use std::{fs::{Metadata, OpenOptions}, io, mem, os::windows::{fs::OpenOptionsExt, io::AsRawHandle}, ffi::c_void, path::Path};
+use winapi::um::{
+	winbase::{GetFileInformationByHandleEx, FILE_FLAG_BACKUP_SEMANTICS, FILE_FLAG_OPEN_REPARSE_POINT},
+	minwinbase::FileIdInfo,
+	fileapi::FILE_ID_INFO
+};
 use crate::{GetID, FileID};
 
 pub type FileIDImpl = (u64, u128);
@@ -22,3 +26,26 @@ impl<T: AsRawHandle> GetID for T {
 		}
 	}
 }
+
+pub fn get_metadata_id(_metadata: &Metadata) -> Option<FileID> {
+	// `MetadataExt::volume_serial_number` and `MetadataExt::file_index` are not stable yet.
+	None
+}
+
+pub fn get_path_id(path: &Path) -> io::Result<FileID> {
+	open_for_id(path, FILE_FLAG_BACKUP_SEMANTICS)
+}
+
+pub fn get_id_nofollow(path: &Path) -> io::Result<FileID> {
+	open_for_id(path, FILE_FLAG_BACKUP_SEMANTICS | FILE_FLAG_OPEN_REPARSE_POINT)
+}
+
+fn open_for_id(path: &Path, flags: u32) -> io::Result<FileID> {
+	// No access rights are needed to query the file's identifier,
+	// and `FILE_FLAG_BACKUP_SEMANTICS` is required to open directories.
+	OpenOptions::new()
+		.access_mode(0)
+		.custom_flags(flags)
+		.open(path)?
+		.get_id()
+}